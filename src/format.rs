@@ -0,0 +1,1538 @@
+use crate::{format_date, HistoryStats, PullRequest, SearchMode};
+use anyhow::Result;
+use clap::ValueEnum;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, BTreeSet, HashMap, HashSet};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, ValueEnum, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum OutputFormat {
+    /// Human-readable list of PRs (default)
+    #[default]
+    Text,
+    /// Number of matching PRs per `org/repo`, busiest first
+    CountByRepo,
+    /// JSON array of PRs, suitable as a `--baseline` snapshot
+    Json,
+    /// PRs added/removed relative to the `--baseline` snapshot
+    Delta,
+    /// GitHub Actions workflow annotations, one per PR
+    GithubActions,
+    /// XML document with one `<pr>` element per PR
+    Xml,
+    /// Unique `org/repo` paths that have matching PRs, one per line
+    Paths,
+    /// Just each PR's URL, one per line
+    Urls,
+    /// TOML document with a `[[prs]]` table per PR
+    Toml,
+    /// Markdown task list with a `- [ ]` item per PR
+    MarkdownChecklist,
+    /// SwiftBar/xbar menu: PR count as the title, PRs as clickable items per repository
+    Swiftbar,
+    /// PRs grouped into review required / changes requested / approved
+    Kanban,
+    /// A single line with the number of PRs and the oldest one's age
+    SummaryOnly,
+    /// An Obsidian note: date frontmatter and a task per PR with a wikilink
+    Obsidian,
+    /// reStructuredText simple table: number, title, author, link
+    Restructuredtext,
+    /// AsciiDoc table: number, title, author, link
+    Asciidoc,
+    /// An unstyled HTML `<ul>` of PR links, for embedding in a page
+    HtmlFragment,
+    /// JSON array of the matching PRs exactly as `gh pr list` reported them
+    RawGh,
+    /// Discord webhook payload: the summary line and an embed per PR
+    Discord,
+    /// `#NUMBER TITLE (AUTHOR) URL` per line, without emoji or blank lines
+    Plain,
+    /// A fenced Mermaid gantt chart with a bar per PR from its opening to today
+    Mermaid,
+    /// OPML outline for outliners: a node per repository with a link node per PR
+    Opml,
+    /// Pandoc JSON AST: a bullet list with a link per PR
+    PandocJson,
+    /// Nothing at all; the exit code tells whether any PRs were found
+    None,
+}
+
+impl OutputFormat {
+    /// Whether stdout carries machine-readable results that progress output must not mix into.
+    pub fn is_machine_readable(self) -> bool {
+        matches!(
+            self,
+            OutputFormat::Json
+                | OutputFormat::GithubActions
+                | OutputFormat::Xml
+                | OutputFormat::Paths
+                | OutputFormat::Urls
+                | OutputFormat::Toml
+                | OutputFormat::MarkdownChecklist
+                | OutputFormat::Swiftbar
+                | OutputFormat::SummaryOnly
+                | OutputFormat::Obsidian
+                | OutputFormat::Restructuredtext
+                | OutputFormat::Asciidoc
+                | OutputFormat::HtmlFragment
+                | OutputFormat::RawGh
+                | OutputFormat::Discord
+                | OutputFormat::Mermaid
+                | OutputFormat::Opml
+                | OutputFormat::PandocJson
+                | OutputFormat::Plain
+                | OutputFormat::None
+        )
+    }
+
+    /// Whether stdout carries JSON, so errors are reported as JSON too.
+    pub fn is_json(self) -> bool {
+        matches!(
+            self,
+            OutputFormat::Json | OutputFormat::RawGh | OutputFormat::PandocJson
+        )
+    }
+}
+
+/// Splits an `--output` value, `FILE:FORMAT`, at its last colon.
+pub fn parse_output_spec(spec: &str) -> Result<(PathBuf, OutputFormat)> {
+    let (path, format) = spec
+        .rsplit_once(':')
+        .filter(|(path, _)| !path.is_empty())
+        .ok_or_else(|| anyhow::anyhow!("'{}' is not FILE:FORMAT, e.g. report.json:json", spec))?;
+    let format = OutputFormat::from_str(format, true).map_err(|_| {
+        anyhow::anyhow!(
+            "'{}' is not an output format (one of: {})",
+            format,
+            OutputFormat::value_variants()
+                .iter()
+                .filter_map(|variant| variant.to_possible_value())
+                .map(|value| value.get_name().to_string())
+                .collect::<Vec<_>>()
+                .join(", ")
+        )
+    })?;
+    Ok((PathBuf::from(path), format))
+}
+
+/// Whether to color the text output: `auto` colors only a terminal, and
+/// only when `NO_COLOR` isn't set.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, ValueEnum)]
+pub enum ColorChoice {
+    #[default]
+    Auto,
+    Always,
+    Never,
+}
+
+/// How long, in days, a PR can wait before the text output shows it yellow
+/// and then red (the `[age_colors]` section of the config).
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq)]
+#[serde(default)]
+pub struct AgeColors {
+    /// PRs waiting at least this many days are yellow, newer ones green.
+    pub yellow: u64,
+    /// PRs waiting more than this many days are red.
+    pub red: u64,
+}
+
+impl Default for AgeColors {
+    fn default() -> Self {
+        Self { yellow: 1, red: 3 }
+    }
+}
+
+impl AgeColors {
+    pub fn is_default(&self) -> bool {
+        *self == AgeColors::default()
+    }
+
+    /// The ANSI color code for a PR that has waited `age_secs`.
+    fn ansi_code(&self, age_secs: u64) -> &'static str {
+        if age_secs < self.yellow * 86_400 {
+            "32"
+        } else if age_secs <= self.red * 86_400 {
+            "33"
+        } else {
+            "31"
+        }
+    }
+}
+
+/// `url` with its scheme and host replaced by `base`, e.g. `/` for
+/// host-relative links. URLs without a scheme are returned as they are.
+pub fn rebase_url(url: &str, base: &str) -> String {
+    let Some((_, rest)) = url.split_once("://") else {
+        return url.to_string();
+    };
+    let path = rest.find('/').map_or("", |slash| &rest[slash + 1..]);
+    format!("{}/{}", base.trim_end_matches('/'), path)
+}
+
+/// `title` cut to at most `width` characters, ending in an ellipsis when
+/// anything was cut.
+pub fn truncate_title(title: &str, width: usize) -> String {
+    if title.chars().count() <= width {
+        return title.to_string();
+    }
+    let mut truncated: String = title.chars().take(width.saturating_sub(1)).collect();
+    truncated.push('…');
+    truncated
+}
+
+/// `text` as an OSC 8 terminal hyperlink to `url`.
+pub fn hyperlink(text: &str, url: &str) -> String {
+    format!("\x1b]8;;{}\x1b\\{}\x1b]8;;\x1b\\", url, text)
+}
+
+/// The default human-readable listing, one block per PR. With `colors`, each
+/// PR whose age is known gets its heading colored by how long it has waited.
+/// With `links`, each title is a terminal hyperlink to the PR and the URL
+/// line is left out.
+pub fn render_text(
+    prs: &[PullRequest],
+    now: u64,
+    colors: Option<&AgeColors>,
+    links: bool,
+) -> String {
+    let mut out = String::new();
+    for pr in prs {
+        let title = if links {
+            hyperlink(&pr.title, &pr.html_url)
+        } else {
+            pr.title.clone()
+        };
+        let mut heading = format!("🔗 #{} - {}", pr.number, title);
+        if pr.has_conflicts {
+            heading.push_str(" ⚠️  conflicts");
+        }
+        match (colors, pr.age_secs(now)) {
+            (Some(colors), Some(age)) => out.push_str(&format!(
+                "\x1b[{}m{}\x1b[0m\n",
+                colors.ansi_code(age),
+                heading
+            )),
+            _ => out.push_str(&format!("{}\n", heading)),
+        }
+        out.push_str(&format!("   👤 Author: {}\n", pr.user.login));
+        match pr.age_days(now) {
+            Some(0) => out.push_str("   ⏳ Waiting: less than a day\n"),
+            Some(1) => out.push_str("   ⏳ Waiting: 1 day\n"),
+            Some(days) => out.push_str(&format!("   ⏳ Waiting: {} days\n", days)),
+            None => {}
+        }
+        match (pr.over_sla(now), pr.sla_hours) {
+            (Some(false), Some(hours)) => {
+                out.push_str(&format!("   🟢 Within the {}h review SLA\n", hours))
+            }
+            (Some(true), Some(hours)) => {
+                out.push_str(&format!("   🔴 Over the {}h review SLA\n", hours))
+            }
+            _ => {}
+        }
+        if pr.closed_without_review() {
+            out.push_str(&format!(
+                "   🔒 {} without your review\n",
+                pr.state.as_deref().unwrap_or_default().to_lowercase()
+            ));
+        }
+        if let Some(reviewer) = &pr.requested_reviewer {
+            out.push_str(&format!("   🙋 Waiting for: {}\n", reviewer));
+        }
+        if !pr.reasons.is_empty() {
+            let reasons: Vec<&str> = pr.reasons.iter().map(|reason| reason.name()).collect();
+            out.push_str(&format!("   📥 In your inbox as: {}\n", reasons.join(", ")));
+        }
+        if let Some(team) = &pr.requested_team {
+            out.push_str(&format!("   👥 Requested via team: {}\n", team));
+        }
+        if let Some(host) = &pr.host {
+            out.push_str(&format!("   🖥️  Host: {}\n", host));
+        }
+        if !pr.explanation.is_empty() {
+            out.push_str(&format!(
+                "   💡 Shown because: {}\n",
+                pr.explanation.join("; ")
+            ));
+        }
+        if links {
+            out.push('\n');
+        } else {
+            out.push_str(&format!("   🌐 URL: {}\n\n", pr.html_url));
+        }
+    }
+    out
+}
+
+/// The `count` longest-waiting PRs as a "most overdue" section to show above
+/// the full list. Empty when that wouldn't leave anything out, or no ages are known.
+pub fn render_oldest(prs: &[PullRequest], now: u64, count: usize) -> String {
+    if count == 0 || prs.len() <= count {
+        return String::new();
+    }
+    let mut aged: Vec<(u64, &PullRequest)> = prs
+        .iter()
+        .filter_map(|pr| Some((pr.age_secs(now)?, pr)))
+        .collect();
+    if aged.is_empty() {
+        return String::new();
+    }
+    // Only the oldest few need to be in order
+    if aged.len() > count {
+        aged.select_nth_unstable_by_key(count - 1, |(age, _)| std::cmp::Reverse(*age));
+        aged.truncate(count);
+    }
+    aged.sort_by_key(|(age, _)| std::cmp::Reverse(*age));
+
+    let mut out = String::from("🔥 Most overdue:\n");
+    for (age, pr) in aged {
+        let days = age / 86_400;
+        let waiting = match days {
+            0 => "less than a day".to_string(),
+            1 => "1 day".to_string(),
+            days => format!("{} days", days),
+        };
+        out.push_str(&format!(
+            "   #{} {} ({}), waiting {}\n",
+            pr.number, pr.title, pr.repo, waiting
+        ));
+    }
+    out.push('\n');
+    out
+}
+
+/// One line such as `You have 5 PRs awaiting review (oldest 8 days)`, where
+/// `what` describes the PRs and the age is left out when no creation times are known.
+pub fn render_summary(prs: &[PullRequest], what: &str, now: u64) -> String {
+    if prs.is_empty() {
+        return format!("You have no PRs {}\n", what);
+    }
+    let count = if prs.len() == 1 {
+        "1 PR".to_string()
+    } else {
+        format!("{} PRs", prs.len())
+    };
+    let oldest = match prs.iter().filter_map(|pr| pr.age_days(now)).max() {
+        Some(0) => " (oldest opened today)".to_string(),
+        Some(1) => " (oldest 1 day)".to_string(),
+        Some(days) => format!(" (oldest {} days)", days),
+        None => String::new(),
+    };
+    format!("You have {} {}{}\n", count, what, oldest)
+}
+
+/// `rr stats`: the current backlog and how quickly reviews got done, from
+/// the recorded history.
+pub fn render_stats(stats: &HistoryStats) -> String {
+    let mut out = format!(
+        "📈 {} recorded scan(s) from {} to {}\n",
+        stats.runs,
+        format_date(stats.first_at),
+        format_date(stats.last_at)
+    );
+    out.push_str(&format!("  Current backlog: {} PR(s)", stats.backlog));
+    match stats.oldest_backlog_days {
+        Some(0) => out.push_str(" (oldest opened today)"),
+        Some(1) => out.push_str(" (oldest opened 1 day ago)"),
+        Some(days) => out.push_str(&format!(" (oldest opened {} days ago)", days)),
+        None => {}
+    }
+    out.push('\n');
+    out.push_str(&format!("  Reviewed: {} PR(s)\n", stats.reviewed));
+    if let Some(secs) = stats.average_review_secs {
+        out.push_str(&format!(
+            "  Average time to review: {:.1} days\n",
+            secs as f64 / 86_400.0
+        ));
+    }
+    if !stats.reviewed_per_week.is_empty() {
+        out.push_str("  Reviewed per week:\n");
+        for (week, count) in &stats.reviewed_per_week {
+            out.push_str(&format!("    {}: {}\n", week, count));
+        }
+    }
+    out
+}
+
+/// Kanban columns, in display order, with the review decisions they collect.
+/// PRs without a decision (no required reviews) count as awaiting review.
+const KANBAN_COLUMNS: [(&str, &[&str]); 3] = [
+    ("📝 Review required", &["REVIEW_REQUIRED", ""]),
+    ("🔁 Changes requested", &["CHANGES_REQUESTED"]),
+    ("✅ Approved", &["APPROVED"]),
+];
+
+/// PRs bucketed by review decision, one column after another.
+pub fn render_kanban(prs: &[PullRequest]) -> String {
+    let mut out = String::new();
+    for (heading, decisions) in KANBAN_COLUMNS {
+        let column: Vec<&PullRequest> = prs
+            .iter()
+            .filter(|pr| decisions.contains(&pr.review_decision.as_deref().unwrap_or_default()))
+            .collect();
+        out.push_str(&format!("{} ({})\n", heading, column.len()));
+        for pr in column {
+            out.push_str(&format!("   #{} - {} ({})\n", pr.number, pr.title, pr.repo));
+            out.push_str(&format!("      🌐 {}\n", pr.html_url));
+        }
+        out.push('\n');
+    }
+    out
+}
+
+/// Counts PRs per repository, sorted by count (descending) and then by name.
+pub fn count_by_repo(prs: &[PullRequest]) -> Vec<(String, usize)> {
+    let mut counts: HashMap<&str, usize> = HashMap::new();
+    for pr in prs {
+        *counts.entry(pr.repo.as_str()).or_insert(0) += 1;
+    }
+
+    let mut counts: Vec<(String, usize)> = counts
+        .into_iter()
+        .map(|(repo, count)| (repo.to_string(), count))
+        .collect();
+    counts.sort_by(|a, b| b.1.cmp(&a.1).then_with(|| a.0.cmp(&b.0)));
+    counts
+}
+
+pub fn render_count_by_repo(prs: &[PullRequest]) -> String {
+    count_by_repo(prs)
+        .into_iter()
+        .map(|(repo, count)| format!("{}: {}\n", repo, count))
+        .collect()
+}
+
+/// Each repository with matching PRs once, sorted by name.
+pub fn render_paths(prs: &[PullRequest]) -> String {
+    let paths: BTreeSet<&str> = prs.iter().map(|pr| pr.repo.as_str()).collect();
+    paths
+        .into_iter()
+        .map(|path| format!("{}\n", path))
+        .collect()
+}
+
+/// Each PR's URL on a line of its own, in result order.
+pub fn render_urls(prs: &[PullRequest]) -> String {
+    prs.iter().map(|pr| format!("{}\n", pr.html_url)).collect()
+}
+
+/// One undecorated line per PR, `#12 Fix login (alice) https://...`, to
+/// read in logs and grep.
+pub fn render_plain(prs: &[PullRequest]) -> String {
+    prs.iter()
+        .map(|pr| {
+            format!(
+                "#{} {} ({}) {}\n",
+                pr.number, pr.title, pr.user.login, pr.html_url
+            )
+        })
+        .collect()
+}
+
+/// The settings a result set was produced with, saved alongside it so a
+/// snapshot documents what it covers.
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Default)]
+pub struct RunMetadata {
+    /// Version of `rr` that ran the scan.
+    pub version: String,
+    pub generated_at: String,
+    /// Unset for `--query` runs.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub mode: Option<SearchMode>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub query: Option<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub targets: Vec<TargetMetadata>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub repo_pattern: Option<String>,
+    /// Result-changing flags in effect, as given on the command line.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub filters: Vec<String>,
+}
+
+/// One host's scanned orgs and the login matched there.
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Default)]
+pub struct TargetMetadata {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub host: Option<String>,
+    pub orgs: Vec<String>,
+    pub username: String,
+}
+
+/// The PRs as JSON: `{"metadata": ..., "prs": [...]}`, or just the PR list
+/// when `metadata` is `None`.
+pub fn render_json(prs: &[PullRequest], metadata: Option<&RunMetadata>) -> Result<String> {
+    #[derive(Serialize)]
+    struct WithMetadata<'a> {
+        metadata: &'a RunMetadata,
+        prs: &'a [PullRequest],
+    }
+
+    let json = match metadata {
+        Some(metadata) => serde_json::to_string_pretty(&WithMetadata { metadata, prs })?,
+        None => serde_json::to_string_pretty(prs)?,
+    };
+    Ok(format!("{}\n", json))
+}
+
+/// The PRs' unparsed `gh pr list` objects as one JSON array.
+pub fn render_raw_gh(prs: &[PullRequest]) -> Result<String> {
+    let raw: Vec<&serde_json::Value> = prs.iter().filter_map(|pr| pr.raw.as_ref()).collect();
+    Ok(format!("{}\n", serde_json::to_string_pretty(&raw)?))
+}
+
+/// Most embeds Discord accepts in one webhook message.
+const DISCORD_MAX_EMBEDS: usize = 10;
+
+/// A Discord webhook payload: `summary` as the message and an embed linking
+/// each PR, with its author and opening time. Discord takes at most
+/// [`DISCORD_MAX_EMBEDS`] embeds, so the message says when PRs were left out.
+pub fn render_discord(prs: &[PullRequest], summary: &str) -> Result<String> {
+    let mut content = summary.trim_end().to_string();
+    if prs.len() > DISCORD_MAX_EMBEDS {
+        content.push_str(&format!(" (showing the first {})", DISCORD_MAX_EMBEDS));
+    }
+    let embeds: Vec<serde_json::Value> = prs
+        .iter()
+        .take(DISCORD_MAX_EMBEDS)
+        .map(|pr| {
+            // Discord rejects embed titles over 256 characters
+            let title: String = format!("{}#{} {}", pr.repo, pr.number, pr.title)
+                .chars()
+                .take(256)
+                .collect();
+            let mut embed = serde_json::json!({
+                "title": title,
+                "url": pr.html_url,
+                "author": {"name": pr.user.login},
+            });
+            if let Some(created_at) = &pr.created_at {
+                embed["timestamp"] = created_at.clone().into();
+            }
+            embed
+        })
+        .collect();
+    let payload = serde_json::json!({"content": content, "embeds": embeds});
+    Ok(format!("{}\n", serde_json::to_string_pretty(&payload)?))
+}
+
+/// Pandoc API version the AST from [`render_pandoc_json`] is written for (pandoc 3).
+const PANDOC_API_VERSION: [u32; 3] = [1, 23, 1];
+
+/// A Pandoc JSON document (`pandoc -f json`) holding a bullet list with an
+/// item per PR: a link titled `org/repo#123 title`, then `by author`. With
+/// no PRs the document has no blocks, as an empty list isn't valid.
+pub fn render_pandoc_json(prs: &[PullRequest]) -> Result<String> {
+    // Pandoc splits text into words and spaces
+    fn inlines(text: &str) -> Vec<serde_json::Value> {
+        let mut out = Vec::new();
+        for word in text.split_whitespace() {
+            if !out.is_empty() {
+                out.push(serde_json::json!({"t": "Space"}));
+            }
+            out.push(serde_json::json!({"t": "Str", "c": word}));
+        }
+        out
+    }
+
+    let items: Vec<serde_json::Value> = prs
+        .iter()
+        .map(|pr| {
+            let text = inlines(&format!("{}#{} {}", pr.repo, pr.number, pr.title));
+            let mut plain = vec![serde_json::json!({
+                "t": "Link",
+                "c": [["", [], []], text, [pr.html_url, ""]]
+            })];
+            plain.push(serde_json::json!({"t": "Space"}));
+            plain.extend(inlines(&format!("by {}", pr.user.login)));
+            serde_json::json!([{"t": "Plain", "c": plain}])
+        })
+        .collect();
+    let blocks = if items.is_empty() {
+        Vec::new()
+    } else {
+        vec![serde_json::json!({"t": "BulletList", "c": items})]
+    };
+    let document = serde_json::json!({
+        "pandoc-api-version": PANDOC_API_VERSION,
+        "meta": {},
+        "blocks": blocks
+    });
+    Ok(format!("{}\n", serde_json::to_string(&document)?))
+}
+
+/// `- [ ] [#123 title](url) — author` per PR, to paste into notes and tick off.
+pub fn render_markdown_checklist(prs: &[PullRequest]) -> String {
+    let mut out = String::new();
+    for pr in prs {
+        // Brackets in the title would end the link text early
+        let title = pr.title.replace('[', "\\[").replace(']', "\\]");
+        out.push_str(&format!(
+            "- [ ] [#{} {}]({}) — {}\n",
+            pr.number, title, pr.html_url, pr.user.login
+        ));
+    }
+    out
+}
+
+/// A reStructuredText simple table with a row per PR, or nothing when there
+/// are none (a table needs at least one row).
+pub fn render_restructuredtext(prs: &[PullRequest]) -> String {
+    if prs.is_empty() {
+        return String::new();
+    }
+    let header = ["PR", "Title", "Author", "Link"].map(str::to_string);
+    let rows: Vec<[String; 4]> = prs
+        .iter()
+        .map(|pr| {
+            [
+                pr.number.to_string(),
+                escape_rst(&pr.title),
+                escape_rst(&pr.user.login),
+                // Anonymous (`__`) so PRs sharing a reference don't clash as link targets
+                format!("`{}#{} <{}>`__", pr.repo, pr.number, pr.html_url),
+            ]
+        })
+        .collect();
+
+    let mut widths = header.clone().map(|cell| cell.chars().count());
+    for row in &rows {
+        for (width, cell) in widths.iter_mut().zip(row) {
+            *width = (*width).max(cell.chars().count());
+        }
+    }
+    let border = widths
+        .iter()
+        .map(|width| "=".repeat(*width))
+        .collect::<Vec<_>>()
+        .join("  ");
+    let line = |cells: &[String; 4]| {
+        let padded: Vec<String> = cells
+            .iter()
+            .zip(&widths)
+            .map(|(cell, width)| format!("{:<1$}", cell, width))
+            .collect();
+        format!("{}\n", padded.join("  ").trim_end())
+    };
+
+    let mut out = format!("{}\n{}{}\n", border, line(&header), border);
+    for row in &rows {
+        out.push_str(&line(row));
+    }
+    out.push_str(&format!("{}\n", border));
+    out
+}
+
+/// An AsciiDoc table with a header row and a row per PR, linking each one
+/// with the `url[text]` macro.
+pub fn render_asciidoc(prs: &[PullRequest]) -> String {
+    let mut out =
+        String::from("[cols=\"1,4,2,3\",options=\"header\"]\n|===\n|PR |Title |Author |Link\n");
+    for pr in prs {
+        out.push_str(&format!(
+            "\n|{} |{} |{} |{}[{}]\n",
+            pr.number,
+            escape_asciidoc_cell(&pr.title),
+            escape_asciidoc_cell(&pr.user.login),
+            pr.html_url,
+            escape_asciidoc_cell(&format!("{}#{}", pr.repo, pr.number)).replace(']', "\\]")
+        ));
+    }
+    out.push_str("|===\n");
+    out
+}
+
+/// `text` with the `|` that would start a new table cell escaped.
+fn escape_asciidoc_cell(text: &str) -> String {
+    text.replace('|', "\\|")
+}
+
+/// `text` with the characters that start inline markup backslash-escaped.
+fn escape_rst(text: &str) -> String {
+    let mut out = String::with_capacity(text.len());
+    for c in text.chars() {
+        if matches!(c, '\\' | '*' | '`' | '_' | '|') {
+            out.push('\\');
+        }
+        out.push(c);
+    }
+    out
+}
+
+/// An Obsidian note dated `today`: frontmatter, then per PR a task with a
+/// `[[repo-123 Title]]` wikilink to a note of its own, its URL, author and,
+/// when known, the day it was opened.
+pub fn render_obsidian(prs: &[PullRequest], today: &str) -> String {
+    let mut out = format!(
+        "---\ndate: {}\nprs: {}\ntags: [review-radar]\n---\n\n",
+        today,
+        prs.len()
+    );
+    for pr in prs {
+        let repo = pr.repo.rsplit('/').next().unwrap_or_default();
+        let note = obsidian_note_title(&format!("{}-{} {}", repo, pr.number, pr.title));
+        out.push_str(&format!(
+            "- [ ] [[{}]] — [{}#{}]({}) by {}",
+            note, pr.repo, pr.number, pr.html_url, pr.user.login
+        ));
+        if let Some(date) = pr.created_at.as_deref().and_then(|at| at.get(..10)) {
+            out.push_str(&format!(", opened {}", date));
+        }
+        out.push('\n');
+    }
+    out
+}
+
+/// A ```` ```mermaid ```` gantt chart dated `today`, with a section per
+/// repository and a bar per PR from the day it was opened to today. PRs
+/// without a known opening time have no bar to draw and are left out.
+pub fn render_mermaid(prs: &[PullRequest], today: &str) -> String {
+    let mut out = String::from(
+        "```mermaid\ngantt\n    title PR ages\n    dateFormat YYYY-MM-DD\n    axisFormat %b %d\n",
+    );
+    // Sections in the order their repositories first come up, however sorted
+    let mut sections: Vec<(&str, Vec<String>)> = Vec::new();
+    for pr in prs {
+        let Some(opened) = pr.created_at.as_deref().and_then(|at| at.get(..10)) else {
+            continue;
+        };
+        let task = format!(
+            "    {} :{}, {}\n",
+            escape_mermaid(&format!("#{} {}", pr.number, pr.title)),
+            opened,
+            today
+        );
+        match sections.iter_mut().find(|(repo, _)| *repo == pr.repo) {
+            Some((_, tasks)) => tasks.push(task),
+            None => sections.push((&pr.repo, vec![task])),
+        }
+    }
+    for (repo, tasks) in sections {
+        out.push_str(&format!("    section {}\n", escape_mermaid(repo)));
+        out.extend(tasks);
+    }
+    out.push_str("```\n");
+    out
+}
+
+/// `text` with the characters that end a gantt task name or statement (`:`,
+/// `;`, `#` itself) written as Mermaid entity codes.
+fn escape_mermaid(text: &str) -> String {
+    text.chars()
+        .map(|c| match c {
+            '#' => "#35;".to_string(),
+            ':' => "#58;".to_string(),
+            ';' => "#59;".to_string(),
+            '\n' | '\r' => " ".to_string(),
+            c => c.to_string(),
+        })
+        .collect()
+}
+
+/// `title` without the characters Obsidian doesn't allow in note names
+/// (`#` and `^` would also turn the wikilink into a heading or block link).
+fn obsidian_note_title(title: &str) -> String {
+    let cleaned: String = title
+        .chars()
+        .filter(|c| {
+            !matches!(
+                c,
+                '*' | '"' | '\\' | '/' | '<' | '>' | ':' | '|' | '?' | '#' | '^' | '[' | ']'
+            )
+        })
+        .collect();
+    // Also collapse the gaps removals leave, and leading dots that would hide the file
+    cleaned
+        .split_whitespace()
+        .collect::<Vec<_>>()
+        .join(" ")
+        .trim_start_matches('.')
+        .to_string()
+}
+
+/// A SwiftBar plugin menu: the PR count in the menu bar, and under it each
+/// repository with its PRs as `--` submenu items that open the PR.
+pub fn render_swiftbar(prs: &[PullRequest]) -> String {
+    let mut out = format!("👀 {}\n---\n", prs.len());
+    let mut by_repo: BTreeMap<&str, Vec<&PullRequest>> = BTreeMap::new();
+    for pr in prs {
+        by_repo.entry(pr.repo.as_str()).or_default().push(pr);
+    }
+    for (repo, prs) in by_repo {
+        out.push_str(&format!("{}\n", repo));
+        for pr in prs {
+            // `|` starts SwiftBar's parameters, so it can't appear in the text
+            let title = pr.title.replace('|', "¦");
+            out.push_str(&format!(
+                "--#{} {} | href={}\n",
+                pr.number, title, pr.html_url
+            ));
+        }
+    }
+    out
+}
+
+/// TOML has no top-level arrays, so the PRs go under a `prs` key.
+pub fn render_toml(prs: &[PullRequest]) -> Result<String> {
+    #[derive(Serialize)]
+    struct Document<'a> {
+        prs: &'a [PullRequest],
+    }
+    Ok(toml::to_string_pretty(&Document { prs })?)
+}
+
+/// Reads a result set previously saved with `--format json`, with or
+/// without its metadata.
+pub fn read_snapshot(path: &Path) -> Result<Vec<PullRequest>> {
+    let content = fs::read_to_string(path)
+        .map_err(|e| anyhow::anyhow!("Could not read baseline '{}': {}", path.display(), e))?;
+    match serde_json::from_str(&content) {
+        Ok(Snapshot::Plain(prs)) | Ok(Snapshot::WithMetadata { prs }) => Ok(prs),
+        Err(e) => Err(anyhow::anyhow!(
+            "Invalid baseline '{}': {}",
+            path.display(),
+            e
+        )),
+    }
+}
+
+/// The two shapes `--format json` writes: a bare PR list with `--no-metadata`,
+/// otherwise an object with `metadata` and `prs`.
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum Snapshot {
+    Plain(Vec<PullRequest>),
+    WithMetadata { prs: Vec<PullRequest> },
+}
+
+/// PRs present in `current` but not in `baseline` (added), and vice versa (removed),
+/// compared by URL.
+pub fn delta<'a>(
+    current: &'a [PullRequest],
+    baseline: &'a [PullRequest],
+) -> (Vec<&'a PullRequest>, Vec<&'a PullRequest>) {
+    let current_urls: HashSet<&str> = current.iter().map(|pr| pr.html_url.as_str()).collect();
+    let baseline_urls: HashSet<&str> = baseline.iter().map(|pr| pr.html_url.as_str()).collect();
+
+    let added = current
+        .iter()
+        .filter(|pr| !baseline_urls.contains(pr.html_url.as_str()))
+        .collect();
+    let removed = baseline
+        .iter()
+        .filter(|pr| !current_urls.contains(pr.html_url.as_str()))
+        .collect();
+    (added, removed)
+}
+
+pub fn render_delta(current: &[PullRequest], baseline: &[PullRequest]) -> String {
+    let (added, removed) = delta(current, baseline);
+    if added.is_empty() && removed.is_empty() {
+        return "✅ No changes since baseline\n".to_string();
+    }
+
+    let mut out = format!(
+        "📋 Changes since baseline: {} added, {} removed\n\n",
+        added.len(),
+        removed.len()
+    );
+    for (marker, prs) in [("➕", added), ("➖", removed)] {
+        for pr in prs {
+            out.push_str(&format!("{} #{} - {}\n", marker, pr.number, pr.title));
+            out.push_str(&format!("   🌐 URL: {}\n", pr.html_url));
+        }
+    }
+    out
+}
+
+/// Escapes annotation message data per the workflow command syntax.
+fn escape_actions_data(s: &str) -> String {
+    s.replace('%', "%25")
+        .replace('\r', "%0D")
+        .replace('\n', "%0A")
+}
+
+/// Escapes annotation properties, which additionally can't contain `:` or `,`.
+fn escape_actions_property(s: &str) -> String {
+    escape_actions_data(s)
+        .replace(':', "%3A")
+        .replace(',', "%2C")
+}
+
+/// `::notice::` annotations per PR, or `::warning::` for PRs with conflicts,
+/// so they surface in the GitHub Actions UI.
+pub fn render_github_actions(prs: &[PullRequest]) -> String {
+    let mut out = String::new();
+    for pr in prs {
+        let level = if pr.has_conflicts {
+            "warning"
+        } else {
+            "notice"
+        };
+        let title = format!("{}#{}", pr.repo, pr.number);
+        let message = format!("{} by {}: {}", pr.title, pr.user.login, pr.html_url);
+        out.push_str(&format!(
+            "::{} title={}::{}\n",
+            level,
+            escape_actions_property(&title),
+            escape_actions_data(&message)
+        ));
+    }
+    out
+}
+
+fn escape_xml(s: &str) -> String {
+    s.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+        .replace('\'', "&apos;")
+}
+
+/// A bare `<ul>` with an `<li>` linking each PR, without any page wrapper
+/// or styling, to drop into an existing template.
+pub fn render_html_fragment(prs: &[PullRequest]) -> String {
+    let mut out = String::from("<ul>\n");
+    for pr in prs {
+        out.push_str(&format!(
+            "  <li><a href=\"{}\">{}#{} {}</a> by {}</li>\n",
+            escape_xml(&pr.html_url),
+            escape_xml(&pr.repo),
+            pr.number,
+            escape_xml(&pr.title),
+            escape_xml(&pr.user.login)
+        ));
+    }
+    out.push_str("</ul>\n");
+    out
+}
+
+/// An OPML 2.0 document with an outline node per repository, in the order
+/// they first come up, holding a link node per PR.
+pub fn render_opml(prs: &[PullRequest]) -> String {
+    let mut out = String::from(
+        "<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n<opml version=\"2.0\">\n  <head>\n    <title>Review Radar</title>\n  </head>\n  <body>\n",
+    );
+    let mut repos: Vec<(&str, Vec<&PullRequest>)> = Vec::new();
+    for pr in prs {
+        match repos.iter_mut().find(|(repo, _)| *repo == pr.repo) {
+            Some((_, repo_prs)) => repo_prs.push(pr),
+            None => repos.push((&pr.repo, vec![pr])),
+        }
+    }
+    for (repo, repo_prs) in repos {
+        out.push_str(&format!("    <outline text=\"{}\">\n", escape_xml(repo)));
+        for pr in repo_prs {
+            out.push_str(&format!(
+                "      <outline text=\"{}\" type=\"link\" url=\"{}\"/>\n",
+                escape_xml(&format!("#{} {} ({})", pr.number, pr.title, pr.user.login)),
+                escape_xml(&pr.html_url)
+            ));
+        }
+        out.push_str("    </outline>\n");
+    }
+    out.push_str("  </body>\n</opml>\n");
+    out
+}
+
+/// A `<prs>` document with one `<pr>` element per PR; optional fields are
+/// left out when unset.
+pub fn render_xml(prs: &[PullRequest]) -> String {
+    let mut out = String::from("<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n<prs>\n");
+    for pr in prs {
+        let mut fields = vec![
+            ("number", pr.number.to_string()),
+            ("title", pr.title.clone()),
+            ("url", pr.html_url.clone()),
+            ("author", pr.user.login.clone()),
+            ("repo", pr.repo.clone()),
+        ];
+        if let Some(host) = &pr.host {
+            fields.push(("host", host.clone()));
+        }
+        if let Some(team) = &pr.requested_team {
+            fields.push(("requested_team", team.clone()));
+        }
+        fields.push(("has_conflicts", pr.has_conflicts.to_string()));
+
+        out.push_str("  <pr>\n");
+        for (name, value) in fields {
+            out.push_str(&format!("    <{0}>{1}</{0}>\n", name, escape_xml(&value)));
+        }
+        out.push_str("  </pr>\n");
+    }
+    out.push_str("</prs>\n");
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{parse_timestamp, InboxReason, User};
+
+    fn pr(number: u32, repo: &str) -> PullRequest {
+        PullRequest {
+            number,
+            title: format!("PR {}", number),
+            html_url: format!("https://github.com/{}/pull/{}", repo, number),
+            user: User {
+                login: "author".to_string(),
+            },
+            repo: repo.to_string(),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_rebase_url() {
+        let url = "https://github.example.com/org/api/pull/7";
+        assert_eq!(rebase_url(url, "/"), "/org/api/pull/7");
+        assert_eq!(
+            rebase_url(url, "https://portal.example.com/gh/"),
+            "https://portal.example.com/gh/org/api/pull/7"
+        );
+        assert_eq!(rebase_url("org/api#7", "/"), "org/api#7");
+    }
+
+    #[test]
+    fn test_truncate_title() {
+        assert_eq!(truncate_title("Fix login", 9), "Fix login");
+        assert_eq!(truncate_title("Fix login bug", 9), "Fix logi…");
+        assert_eq!(truncate_title("Fix ünïcode", 6), "Fix ü…");
+    }
+
+    #[test]
+    fn test_render_text() {
+        let mut conflicting = pr(2, "org/b");
+        conflicting.has_conflicts = true;
+        conflicting.host = Some("github.example.com".to_string());
+        conflicting.requested_team = Some("org/core".to_string());
+        conflicting.requested_reviewer = Some("bob".to_string());
+        conflicting.reasons = vec![InboxReason::Review, InboxReason::Mention];
+        conflicting.explanation = vec![
+            "review requested from me".to_string(),
+            "repo is public".to_string(),
+        ];
+
+        let rendered = render_text(&[pr(1, "org/a"), conflicting], 0, None, false);
+        assert_eq!(
+            rendered,
+            "🔗 #1 - PR 1\n   👤 Author: author\n   🌐 URL: https://github.com/org/a/pull/1\n\n\
+             🔗 #2 - PR 2 ⚠️  conflicts\n   👤 Author: author\n   🙋 Waiting for: bob\n   📥 In your inbox as: review, mention\n   👥 Requested via team: org/core\n   🖥️  Host: github.example.com\n   💡 Shown because: review requested from me; repo is public\n   🌐 URL: https://github.com/org/b/pull/2\n\n"
+        );
+    }
+
+    #[test]
+    fn test_render_oldest() {
+        let opened = |number: u32, created_at: &str| PullRequest {
+            created_at: Some(created_at.to_string()),
+            ..pr(number, "org/a")
+        };
+        let prs = vec![
+            opened(1, "2024-01-14T00:00:00Z"),
+            opened(2, "2024-01-01T00:00:00Z"),
+            pr(3, "org/a"),
+            opened(4, "2024-01-10T00:00:00Z"),
+            opened(5, "2024-01-15T00:00:00Z"),
+        ];
+        let now = parse_timestamp("2024-01-15T12:00:00Z").unwrap();
+        assert_eq!(
+            render_oldest(&prs, now, 3),
+            "🔥 Most overdue:\n   #2 PR 2 (org/a), waiting 14 days\n   #4 PR 4 (org/a), waiting 5 days\n   #1 PR 1 (org/a), waiting 1 day\n\n"
+        );
+        assert!(render_oldest(&prs, now, 1).contains("#2 PR 2"));
+        // Nothing is left out, or there's nothing to go by
+        assert_eq!(render_oldest(&prs, now, 5), "");
+        assert_eq!(render_oldest(&prs, now, 0), "");
+        assert_eq!(render_oldest(&[pr(1, "org/a"), pr(2, "org/a")], now, 1), "");
+    }
+
+    #[test]
+    fn test_render_text_sla() {
+        let opened = 1_705_314_600;
+        let mut waiting = pr(1, "org/a");
+        waiting.created_at = Some("2024-01-15T10:30:00Z".to_string());
+        waiting.sla_hours = Some(24);
+        assert!(render_text(&[waiting.clone()], opened + 3_600, None, false)
+            .contains("   ⏳ Waiting: less than a day\n   🟢 Within the 24h review SLA\n"));
+        assert!(
+            render_text(&[waiting.clone()], opened + 2 * 86_400, None, false)
+                .contains("   🔴 Over the 24h review SLA\n")
+        );
+        waiting.sla_hours = None;
+        assert!(!render_text(&[waiting], opened, None, false).contains("SLA"));
+    }
+
+    #[test]
+    fn test_render_text_hyperlinks() {
+        let rendered = render_text(&[pr(1, "org/a")], 0, None, true);
+        assert_eq!(
+            rendered,
+            "🔗 #1 - \x1b]8;;https://github.com/org/a/pull/1\x1b\\PR 1\x1b]8;;\x1b\\\n   👤 Author: author\n\n"
+        );
+    }
+
+    #[test]
+    fn test_render_text_age_colors() {
+        let opened = 1_705_314_600;
+        let mut waiting = pr(1, "org/a");
+        waiting.created_at = Some("2024-01-15T10:30:00Z".to_string());
+        let colors = AgeColors::default();
+        let heading = |age: u64| {
+            let rendered = render_text(&[waiting.clone()], opened + age, Some(&colors), false);
+            rendered.lines().next().unwrap().to_string()
+        };
+
+        assert_eq!(heading(3_600), "\x1b[32m🔗 #1 - PR 1\x1b[0m");
+        assert_eq!(heading(86_400), "\x1b[33m🔗 #1 - PR 1\x1b[0m");
+        assert_eq!(heading(3 * 86_400), "\x1b[33m🔗 #1 - PR 1\x1b[0m");
+        assert_eq!(heading(3 * 86_400 + 1), "\x1b[31m🔗 #1 - PR 1\x1b[0m");
+        assert!(
+            render_text(&[waiting.clone()], opened + 2 * 86_400, None, false)
+                .starts_with("🔗 #1 - PR 1\n   👤 Author: author\n   ⏳ Waiting: 2 days\n")
+        );
+        // Without a known age there's nothing to color by
+        assert!(render_text(&[pr(2, "org/a")], opened, Some(&colors), false).starts_with("🔗 #2"));
+    }
+
+    #[test]
+    fn test_render_summary() {
+        let now = 1_705_314_600 + 8 * 86_400;
+        let mut old = pr(1, "org/a");
+        old.created_at = Some("2024-01-15T10:30:00Z".to_string());
+        let mut new = pr(2, "org/a");
+        new.created_at = Some("2024-01-22T10:30:00Z".to_string());
+
+        assert_eq!(
+            render_summary(&[new.clone(), old], "awaiting review", now),
+            "You have 2 PRs awaiting review (oldest 8 days)\n"
+        );
+        assert_eq!(
+            render_summary(&[new], "open", now),
+            "You have 1 PR open (oldest 1 day)\n"
+        );
+        assert_eq!(
+            render_summary(&[pr(3, "org/b")], "open", now),
+            "You have 1 PR open\n"
+        );
+        assert_eq!(
+            render_summary(&[], "awaiting review", now),
+            "You have no PRs awaiting review\n"
+        );
+    }
+
+    #[test]
+    fn test_render_kanban() {
+        let mut approved = pr(2, "org/b");
+        approved.review_decision = Some("APPROVED".to_string());
+        let mut required = pr(3, "org/a");
+        required.review_decision = Some("REVIEW_REQUIRED".to_string());
+
+        assert_eq!(
+            render_kanban(&[pr(1, "org/a"), approved, required]),
+            "📝 Review required (2)\n\
+             \x20  #1 - PR 1 (org/a)\n      🌐 https://github.com/org/a/pull/1\n\
+             \x20  #3 - PR 3 (org/a)\n      🌐 https://github.com/org/a/pull/3\n\n\
+             🔁 Changes requested (0)\n\n\
+             ✅ Approved (1)\n\
+             \x20  #2 - PR 2 (org/b)\n      🌐 https://github.com/org/b/pull/2\n\n"
+        );
+    }
+
+    #[test]
+    fn test_count_by_repo_sorted() {
+        let prs = vec![
+            pr(1, "org1/api"),
+            pr(2, "org2/web"),
+            pr(3, "org1/api"),
+            pr(4, "org1/docs"),
+            pr(5, "org2/web"),
+            pr(6, "org1/api"),
+        ];
+
+        let counts = count_by_repo(&prs);
+        assert_eq!(
+            counts,
+            vec![
+                ("org1/api".to_string(), 3),
+                ("org2/web".to_string(), 2),
+                ("org1/docs".to_string(), 1),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_render_count_by_repo() {
+        let prs = vec![pr(1, "org/b"), pr(2, "org/a")];
+        assert_eq!(render_count_by_repo(&prs), "org/a: 1\norg/b: 1\n");
+        assert_eq!(render_count_by_repo(&[]), "");
+    }
+
+    #[test]
+    fn test_render_paths() {
+        let prs = vec![pr(1, "org/b"), pr(2, "org/a"), pr(3, "org/b")];
+        assert_eq!(render_paths(&prs), "org/a\norg/b\n");
+        assert_eq!(render_paths(&[]), "");
+    }
+
+    #[test]
+    fn test_render_plain() {
+        let prs = vec![pr(2, "org/b"), pr(1, "org/a")];
+        assert_eq!(
+            render_plain(&prs),
+            "#2 PR 2 (author) https://github.com/org/b/pull/2\n#1 PR 1 (author) https://github.com/org/a/pull/1\n"
+        );
+        assert_eq!(render_plain(&[]), "");
+    }
+
+    #[test]
+    fn test_render_urls() {
+        let prs = vec![pr(2, "org/b"), pr(1, "org/a")];
+        assert_eq!(
+            render_urls(&prs),
+            "https://github.com/org/b/pull/2\nhttps://github.com/org/a/pull/1\n"
+        );
+        assert_eq!(render_urls(&[]), "");
+    }
+
+    #[test]
+    fn test_render_markdown_checklist() {
+        let mut bracketed = pr(2, "org/b");
+        bracketed.title = "[WIP] Refactor".to_string();
+
+        assert_eq!(
+            render_markdown_checklist(&[pr(1, "org/a"), bracketed]),
+            "- [ ] [#1 PR 1](https://github.com/org/a/pull/1) — author\n\
+             - [ ] [#2 \\[WIP\\] Refactor](https://github.com/org/b/pull/2) — author\n"
+        );
+        assert_eq!(render_markdown_checklist(&[]), "");
+    }
+
+    #[test]
+    fn test_render_stats() {
+        let stats = HistoryStats {
+            runs: 3,
+            first_at: 1_705_276_800,
+            last_at: 1_705_881_600,
+            backlog: 1,
+            oldest_backlog_days: Some(9),
+            reviewed: 2,
+            average_review_secs: Some(4 * 86_400 + 43_200),
+            reviewed_per_week: BTreeMap::from([
+                ("2024-01-15".to_string(), 1),
+                ("2024-01-22".to_string(), 1),
+            ]),
+        };
+        assert_eq!(
+            render_stats(&stats),
+            "📈 3 recorded scan(s) from 2024-01-15 to 2024-01-22\n  Current backlog: 1 PR(s) (oldest opened 9 days ago)\n  Reviewed: 2 PR(s)\n  Average time to review: 4.5 days\n  Reviewed per week:\n    2024-01-15: 1\n    2024-01-22: 1\n"
+        );
+
+        let fresh = HistoryStats {
+            oldest_backlog_days: Some(0),
+            ..stats
+        };
+        assert!(render_stats(&fresh).contains("1 PR(s) (oldest opened today)\n"));
+    }
+
+    #[test]
+    fn test_render_raw_gh() {
+        let mut with_raw = pr(1, "org/a");
+        with_raw.raw = Some(serde_json::json!({"number": 1, "isDraft": true}));
+        let rendered = render_raw_gh(&[with_raw, pr(2, "org/b")]).unwrap();
+        assert_eq!(
+            rendered,
+            "[\n  {\n    \"isDraft\": true,\n    \"number\": 1\n  }\n]\n"
+        );
+        assert_eq!(render_raw_gh(&[]).unwrap(), "[]\n");
+    }
+
+    #[test]
+    fn test_render_discord() {
+        let mut opened = pr(1, "org/a");
+        opened.created_at = Some("2024-01-15T10:30:00Z".to_string());
+        let payload: serde_json::Value = serde_json::from_str(
+            &render_discord(
+                &[opened, pr(2, "org/b")],
+                "You have 2 PRs awaiting review\n",
+            )
+            .unwrap(),
+        )
+        .unwrap();
+        assert_eq!(payload["content"], "You have 2 PRs awaiting review");
+        assert_eq!(payload["embeds"][0]["title"], "org/a#1 PR 1");
+        assert_eq!(
+            payload["embeds"][0]["url"],
+            "https://github.com/org/a/pull/1"
+        );
+        assert_eq!(payload["embeds"][0]["author"]["name"], "author");
+        assert_eq!(payload["embeds"][0]["timestamp"], "2024-01-15T10:30:00Z");
+        assert!(payload["embeds"][1].get("timestamp").is_none());
+
+        let many: Vec<PullRequest> = (1..=12).map(|n| pr(n, "org/a")).collect();
+        let payload: serde_json::Value =
+            serde_json::from_str(&render_discord(&many, "You have 12 PRs").unwrap()).unwrap();
+        assert_eq!(payload["embeds"].as_array().unwrap().len(), 10);
+        assert_eq!(payload["content"], "You have 12 PRs (showing the first 10)");
+    }
+
+    #[test]
+    fn test_render_html_fragment() {
+        let mut tricky = pr(2, "org/b");
+        tricky.title = "Fix <br> & \"quotes\"".to_string();
+
+        assert_eq!(
+            render_html_fragment(&[pr(1, "org/a"), tricky]),
+            "<ul>\n  <li><a href=\"https://github.com/org/a/pull/1\">org/a#1 PR 1</a> by author</li>\n  \
+             <li><a href=\"https://github.com/org/b/pull/2\">org/b#2 Fix &lt;br&gt; &amp; &quot;quotes&quot;</a> by author</li>\n</ul>\n"
+        );
+        assert_eq!(render_html_fragment(&[]), "<ul>\n</ul>\n");
+    }
+
+    #[test]
+    fn test_render_asciidoc() {
+        let mut piped = pr(2, "org/b");
+        piped.title = "Support a|b [draft]".to_string();
+        assert_eq!(
+            render_asciidoc(&[pr(1, "org/a"), piped]),
+            "[cols=\"1,4,2,3\",options=\"header\"]\n|===\n|PR |Title |Author |Link\n\
+             \n|1 |PR 1 |author |https://github.com/org/a/pull/1[org/a#1]\n\
+             \n|2 |Support a\\|b [draft] |author |https://github.com/org/b/pull/2[org/b#2]\n|===\n"
+        );
+        assert_eq!(
+            render_asciidoc(&[]),
+            "[cols=\"1,4,2,3\",options=\"header\"]\n|===\n|PR |Title |Author |Link\n|===\n"
+        );
+    }
+
+    #[test]
+    fn test_render_restructuredtext() {
+        let mut marked_up = pr(12, "org/b");
+        marked_up.title = "Use *fast* `path`".to_string();
+
+        assert_eq!(
+            render_restructuredtext(&[pr(1, "org/a"), marked_up]),
+            "==  =====================  ======  ===============================================\n\
+             PR  Title                  Author  Link\n\
+             ==  =====================  ======  ===============================================\n\
+             1   PR 1                   author  `org/a#1 <https://github.com/org/a/pull/1>`__\n\
+             12  Use \\*fast\\* \\`path\\`  author  `org/b#12 <https://github.com/org/b/pull/12>`__\n\
+             ==  =====================  ======  ===============================================\n"
+        );
+        assert_eq!(render_restructuredtext(&[]), "");
+    }
+
+    #[test]
+    fn test_render_mermaid() {
+        let mut first = pr(1, "org/a");
+        first.title = "fix: parse a;b #2".to_string();
+        first.created_at = Some("2024-01-15T10:30:00Z".to_string());
+        let mut second = pr(2, "org/a");
+        second.created_at = Some("2024-01-20T08:00:00Z".to_string());
+        let mut other_repo = pr(3, "org/b");
+        other_repo.created_at = Some("2024-01-21T08:00:00Z".to_string());
+        let unknown_age = pr(4, "org/c");
+
+        assert_eq!(
+            render_mermaid(&[first, other_repo, unknown_age, second], "2024-01-22"),
+            "```mermaid\ngantt\n    title PR ages\n    dateFormat YYYY-MM-DD\n    axisFormat %b %d\n\
+             \x20   section org/a\n\
+             \x20   #35;1 fix#58; parse a#59;b #35;2 :2024-01-15, 2024-01-22\n\
+             \x20   #35;2 PR 2 :2024-01-20, 2024-01-22\n\
+             \x20   section org/b\n\
+             \x20   #35;3 PR 3 :2024-01-21, 2024-01-22\n```\n"
+        );
+    }
+
+    #[test]
+    fn test_render_obsidian() {
+        let mut tricky = pr(7, "org/api");
+        tricky.title = "fix: [WIP] a/b #1 ^x?".to_string();
+        tricky.created_at = Some("2024-01-15T10:30:00Z".to_string());
+
+        assert_eq!(
+            render_obsidian(&[pr(1, "org/web"), tricky], "2024-01-20"),
+            "---\ndate: 2024-01-20\nprs: 2\ntags: [review-radar]\n---\n\n\
+             - [ ] [[web-1 PR 1]] — [org/web#1](https://github.com/org/web/pull/1) by author\n\
+             - [ ] [[api-7 fix WIP ab 1 x]] — [org/api#7](https://github.com/org/api/pull/7) by author, opened 2024-01-15\n"
+        );
+        assert_eq!(obsidian_note_title(".hidden: note"), "hidden note");
+    }
+
+    #[test]
+    fn test_render_swiftbar() {
+        let mut piped = pr(3, "org/a");
+        piped.title = "a | b".to_string();
+
+        assert_eq!(
+            render_swiftbar(&[pr(2, "org/b"), pr(1, "org/a"), piped]),
+            "👀 3\n---\norg/a\n\
+             --#1 PR 1 | href=https://github.com/org/a/pull/1\n\
+             --#3 a ¦ b | href=https://github.com/org/a/pull/3\n\
+             org/b\n\
+             --#2 PR 2 | href=https://github.com/org/b/pull/2\n"
+        );
+        assert_eq!(render_swiftbar(&[]), "👀 0\n---\n");
+    }
+
+    #[test]
+    fn test_render_toml_round_trips() {
+        #[derive(Deserialize)]
+        struct Document {
+            prs: Vec<PullRequest>,
+        }
+
+        let mut conflicting = pr(2, "org/b");
+        conflicting.has_conflicts = true;
+        conflicting.host = Some("github.example.com".to_string());
+        let prs = vec![pr(1, "org/a"), conflicting];
+
+        let rendered = render_toml(&prs).unwrap();
+        assert!(rendered.contains("[[prs]]"));
+        assert_eq!(toml::from_str::<Document>(&rendered).unwrap().prs, prs);
+        assert!(toml::from_str::<Document>(&render_toml(&[]).unwrap())
+            .unwrap()
+            .prs
+            .is_empty());
+    }
+
+    #[test]
+    fn test_json_round_trips_through_snapshot() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let path = temp_dir.path().join("snapshot.json");
+        let prs = vec![pr(1, "org/a"), pr(2, "org/b")];
+
+        fs::write(&path, render_json(&prs, None).unwrap()).unwrap();
+        assert_eq!(read_snapshot(&path).unwrap(), prs);
+        let metadata = RunMetadata {
+            mode: Some(SearchMode::Own),
+            ..Default::default()
+        };
+        fs::write(&path, render_json(&prs, Some(&metadata)).unwrap()).unwrap();
+        assert_eq!(read_snapshot(&path).unwrap(), prs);
+        assert!(read_snapshot(&temp_dir.path().join("missing.json")).is_err());
+    }
+
+    #[test]
+    fn test_delta() {
+        let baseline = vec![pr(1, "org/a"), pr(2, "org/a")];
+        let current = vec![pr(2, "org/a"), pr(3, "org/b")];
+
+        let (added, removed) = delta(&current, &baseline);
+        assert_eq!(added.iter().map(|pr| pr.number).collect::<Vec<_>>(), [3]);
+        assert_eq!(removed.iter().map(|pr| pr.number).collect::<Vec<_>>(), [1]);
+
+        let rendered = render_delta(&current, &baseline);
+        assert!(rendered.contains("1 added, 1 removed"));
+        assert!(rendered.contains("➕ #3 - PR 3"));
+        assert!(rendered.contains("➖ #1 - PR 1"));
+
+        assert_eq!(
+            render_delta(&current, &current),
+            "✅ No changes since baseline\n"
+        );
+    }
+
+    #[test]
+    fn test_render_github_actions() {
+        let mut conflicting = pr(2, "org/b");
+        conflicting.has_conflicts = true;
+        conflicting.title = "50% done\nmore".to_string();
+
+        assert_eq!(
+            render_github_actions(&[pr(1, "org/a"), conflicting]),
+            "::notice title=org/a#1::PR 1 by author: https://github.com/org/a/pull/1\n\
+             ::warning title=org/b#2::50%25 done%0Amore by author: https://github.com/org/b/pull/2\n"
+        );
+        assert_eq!(escape_actions_property("a:b,c"), "a%3Ab%2Cc");
+        assert_eq!(render_github_actions(&[]), "");
+    }
+
+    #[test]
+    fn test_parse_output_spec() {
+        assert_eq!(
+            parse_output_spec("report.json:json").unwrap(),
+            (PathBuf::from("report.json"), OutputFormat::Json)
+        );
+        assert_eq!(
+            parse_output_spec("C:\\reports\\prs.txt:TEXT").unwrap(),
+            (PathBuf::from("C:\\reports\\prs.txt"), OutputFormat::Text)
+        );
+        assert!(parse_output_spec("report.json").is_err());
+        assert!(parse_output_spec(":json").is_err());
+        let err = parse_output_spec("out.x:yaml").unwrap_err().to_string();
+        assert!(err.contains("one of: text,"), "{}", err);
+    }
+
+    #[test]
+    fn test_render_pandoc_json() {
+        let rendered = render_pandoc_json(&[pr(1, "org/a")]).unwrap();
+        assert!(rendered.ends_with("}\n"));
+        let document: serde_json::Value = serde_json::from_str(&rendered).unwrap();
+        assert_eq!(
+            document["pandoc-api-version"],
+            serde_json::json!([1, 23, 1])
+        );
+        assert_eq!(
+            document["blocks"],
+            serde_json::json!([{"t": "BulletList", "c": [[{"t": "Plain", "c": [
+                {"t": "Link", "c": [["", [], []], [
+                    {"t": "Str", "c": "org/a#1"}, {"t": "Space"},
+                    {"t": "Str", "c": "PR"}, {"t": "Space"}, {"t": "Str", "c": "1"}
+                ], ["https://github.com/org/a/pull/1", ""]]},
+                {"t": "Space"}, {"t": "Str", "c": "by"}, {"t": "Space"}, {"t": "Str", "c": "author"}
+            ]}]]}])
+        );
+
+        let empty: serde_json::Value =
+            serde_json::from_str(&render_pandoc_json(&[]).unwrap()).unwrap();
+        assert_eq!(empty["blocks"], serde_json::json!([]));
+    }
+
+    #[test]
+    fn test_render_opml() {
+        let mut quoted = pr(3, "org/a");
+        quoted.title = "Use \"<T>\" & more".to_string();
+        let rendered = render_opml(&[pr(1, "org/a"), pr(2, "org/b"), quoted]);
+        assert_eq!(
+            rendered,
+            "<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n<opml version=\"2.0\">\n  <head>\n    <title>Review Radar</title>\n  </head>\n  <body>\n\
+             \x20   <outline text=\"org/a\">\n\
+             \x20     <outline text=\"#1 PR 1 (author)\" type=\"link\" url=\"https://github.com/org/a/pull/1\"/>\n\
+             \x20     <outline text=\"#3 Use &quot;&lt;T&gt;&quot; &amp; more (author)\" type=\"link\" url=\"https://github.com/org/a/pull/3\"/>\n\
+             \x20   </outline>\n\
+             \x20   <outline text=\"org/b\">\n\
+             \x20     <outline text=\"#2 PR 2 (author)\" type=\"link\" url=\"https://github.com/org/b/pull/2\"/>\n\
+             \x20   </outline>\n  </body>\n</opml>\n"
+        );
+        assert!(render_opml(&[]).ends_with("  <body>\n  </body>\n</opml>\n"));
+    }
+
+    #[test]
+    fn test_render_xml() {
+        let mut tricky = pr(2, "org/b");
+        tricky.title = "Fix <T> & \"quotes\"".to_string();
+        tricky.requested_team = Some("org/core".to_string());
+
+        let rendered = render_xml(&[pr(1, "org/a"), tricky]);
+        assert!(rendered.starts_with("<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n<prs>\n"));
+        assert!(rendered.contains(
+            "  <pr>
+    <number>1</number>
+    <title>PR 1</title>
+    <url>https://github.com/org/a/pull/1</url>
+    <author>author</author>
+    <repo>org/a</repo>
+    <has_conflicts>false</has_conflicts>
+  </pr>
+"
+        ));
+        assert!(rendered.contains("<title>Fix &lt;T&gt; &amp; &quot;quotes&quot;</title>"));
+        assert!(rendered.contains("<requested_team>org/core</requested_team>"));
+        assert!(rendered.ends_with("</prs>\n"));
+        assert_eq!(rendered.matches("<pr>").count(), 2);
+    }
+}
@@ -1,45 +1,372 @@
 use anyhow::Result;
+use clap::ValueEnum;
 use regex::Regex;
 use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, BTreeSet};
 use std::fs;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
+use std::sync::{mpsc, Mutex};
+use std::thread;
 
-#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub mod format;
+pub mod search;
+
+use format::{AgeColors, OutputFormat};
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Default)]
 pub struct Config {
     pub orgs: Vec<String>,
     pub username: String,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub repo_pattern: Option<String>,
+    /// Several patterns, any of which a repository may match, instead of one
+    /// big alternation in `repo_pattern`.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub repo_patterns: Vec<String>,
+    /// Globs for repositories never to scan, see [`RepoGlob`].
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub exclude_repos: Vec<String>,
+    /// Default values for command-line flags.
+    #[serde(default, skip_serializing_if = "Defaults::is_empty")]
+    pub defaults: Defaults,
+    /// Login to match instead of `username` in specific organizations, for
+    /// orgs where you review from another (e.g. alias) account.
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub org_usernames: BTreeMap<String, String>,
+    /// Friendlier names to show for organizations in progress and status
+    /// lines; `gh` is still given the org itself.
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub org_names: BTreeMap<String, String>,
+    /// Repositories to scan in specific organizations instead of listing
+    /// all of the org's repositories.
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub repos: BTreeMap<String, Vec<String>>,
+    /// When the text output turns waiting PRs yellow and red.
+    #[serde(default, skip_serializing_if = "AgeColors::is_default")]
+    pub age_colors: AgeColors,
+    /// Hours a PR may wait for review in each org before it's over its SLA.
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub review_sla_hours: BTreeMap<String, u64>,
+    /// Additional GitHub hosts to scan alongside the default one.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub hosts: Vec<HostConfig>,
 }
 
-#[derive(Debug, Deserialize)]
+/// Values used for flags that aren't given on the command line (the
+/// `[defaults]` section of the config).
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Default)]
+pub struct Defaults {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub format: Option<OutputFormat>,
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub no_pager: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub pr_limit: Option<usize>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub mode: Option<SearchMode>,
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub legacy_scan: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub sort: Option<SortKey>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub concurrency: Option<usize>,
+}
+
+impl Defaults {
+    pub fn is_empty(&self) -> bool {
+        *self == Defaults::default()
+    }
+}
+
+/// A GitHub host (e.g. a GitHub Enterprise instance) with its own orgs and login.
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Default)]
+pub struct HostConfig {
+    pub host: String,
+    pub orgs: Vec<String>,
+    pub username: String,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Default)]
 pub struct PullRequest {
     pub number: u32,
     pub title: String,
     pub html_url: String,
     pub user: User,
+    /// Repository the PR belongs to, as `org/repo`.
+    #[serde(default)]
+    pub repo: String,
+    /// GitHub host the PR was found on, if not the default one.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub host: Option<String>,
+    /// Whether the PR has merge conflicts with its base branch.
+    #[serde(default)]
+    pub has_conflicts: bool,
+    /// Team (`org/slug`) the review was requested through, when matched via team membership.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub requested_team: Option<String>,
+    /// `REVIEW_REQUIRED`, `CHANGES_REQUESTED` or `APPROVED`, when fetched and
+    /// reported by GitHub.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub review_decision: Option<String>,
+    /// When the PR was opened, as reported by GitHub, when fetched.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub created_at: Option<String>,
+    /// `OPEN`, `CLOSED` or `MERGED`, when fetched.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub state: Option<String>,
+    /// Whose review the PR is waiting for, when looking at other people's
+    /// queues with `--for`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub requested_reviewer: Option<String>,
+    /// The PR exactly as `gh pr list` printed it, kept for `--format raw-gh`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub raw: Option<serde_json::Value>,
+    /// Why the PR is in your inbox, most pressing first, with `--mode inbox`.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub reasons: Vec<InboxReason>,
+    /// Every check the PR passed to be shown, with `--explain`.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub explanation: Vec<String>,
+    /// The review SLA of the PR's org, in hours, if `review_sla_hours` sets one.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub sla_hours: Option<u64>,
 }
 
-#[derive(Debug, Deserialize)]
+impl PullRequest {
+    /// Whether the PR was closed or merged while the review request was
+    /// still pending, i.e. without your review.
+    pub fn closed_without_review(&self) -> bool {
+        self.state.as_deref().is_some_and(|state| state != "OPEN")
+    }
+
+    /// Seconds since the PR was opened, when its creation time is known.
+    pub fn age_secs(&self, now: u64) -> Option<u64> {
+        let created = parse_timestamp(self.created_at.as_deref()?)?;
+        Some(now.saturating_sub(created))
+    }
+
+    /// Whether the PR has waited longer than its org's review SLA, when
+    /// there is one and the PR's age is known.
+    pub fn over_sla(&self, now: u64) -> Option<bool> {
+        Some(self.age_secs(now)? > self.sla_hours? * 3_600)
+    }
+
+    /// Whole days since the PR was opened, when its creation time is known.
+    pub fn age_days(&self, now: u64) -> Option<u64> {
+        Some(self.age_secs(now)? / 86_400)
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Default)]
 pub struct User {
     pub login: String,
 }
 
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
 pub struct GhRepo {
     pub name: String,
+    /// `PUBLIC`, `PRIVATE` or `INTERNAL`, as reported by `gh repo list`.
+    #[serde(default)]
+    pub visibility: Option<String>,
+    /// When something was last pushed, as reported by `gh repo list`.
+    #[serde(rename = "pushedAt", default, skip_serializing_if = "Option::is_none")]
+    pub pushed_at: Option<String>,
+    /// Your access, from `READ` to `ADMIN`, as reported by `gh repo list`.
+    #[serde(
+        rename = "viewerPermission",
+        default,
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub viewer_permission: Option<String>,
     #[serde(skip)]
     pub org: String,
 }
 
+/// Which PRs a scan looks for.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, ValueEnum, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum SearchMode {
+    /// PRs where your review has been requested (default)
+    #[default]
+    Review,
+    /// Your own open PRs
+    Own,
+    /// Both of the above
+    All,
+    /// Everything that needs you: review requests, PRs assigned to you and
+    /// PRs mentioning you, most pressing first
+    Inbox,
+}
+
+impl SearchMode {
+    pub fn includes_review(self) -> bool {
+        matches!(self, SearchMode::Review | SearchMode::All)
+    }
+
+    pub fn includes_own(self) -> bool {
+        matches!(self, SearchMode::Own | SearchMode::All)
+    }
+}
+
+/// Orders for `--sort`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum SortKey {
+    /// Oldest first
+    Age,
+    /// By PR number
+    Number,
+    /// Alphabetically by title
+    Title,
+    /// By `org/repo`, then PR number
+    Repo,
+}
+
+/// Sorts `prs` by `key`. Sorting is stable, so PRs that compare equal keep
+/// the order the scan found them in.
+pub fn sort_prs(prs: &mut [PullRequest], key: SortKey) {
+    match key {
+        // Unknown creation times go last
+        SortKey::Age => prs.sort_by(|a, b| {
+            a.created_at
+                .is_none()
+                .cmp(&b.created_at.is_none())
+                .then_with(|| a.created_at.cmp(&b.created_at))
+        }),
+        SortKey::Number => prs.sort_by_key(|pr| pr.number),
+        SortKey::Title => prs.sort_by_key(|pr| pr.title.to_lowercase()),
+        SortKey::Repo => prs.sort_by(|a, b| {
+            a.repo
+                .to_lowercase()
+                .cmp(&b.repo.to_lowercase())
+                .then(a.number.cmp(&b.number))
+        }),
+    }
+}
+
+/// Why a PR is in the `--mode inbox` view, most pressing first.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum InboxReason {
+    /// Your review was requested.
+    Review,
+    /// You're one of the assignees.
+    Assignee,
+    /// The description @-mentions you.
+    Mention,
+}
+
+impl InboxReason {
+    pub fn name(self) -> &'static str {
+        match self {
+            InboxReason::Review => "review",
+            InboxReason::Assignee => "assignee",
+            InboxReason::Mention => "mention",
+        }
+    }
+}
+
+/// Whether `text` @-mentions `login`, ignoring case. Email addresses and
+/// longer logins starting the same way don't count.
+pub fn mentions(text: &str, login: &str) -> bool {
+    let is_login_char = |c: char| c.is_ascii_alphanumeric() || c == '-';
+    let text = text.to_lowercase();
+    let needle = format!("@{}", login.to_lowercase());
+    text.match_indices(&needle).any(|(i, _)| {
+        let before = text[..i].chars().next_back();
+        let after = text[i + needle.len()..].chars().next();
+        !before.is_some_and(|c| c.is_ascii_alphanumeric()) && !after.is_some_and(is_login_char)
+    })
+}
+
+/// Orders an inbox by its most pressing reason, then oldest first; PRs
+/// without a known creation time go last within their reason.
+pub fn sort_inbox(prs: &mut [PullRequest]) {
+    prs.sort_by(|a, b| {
+        a.reasons
+            .first()
+            .cmp(&b.reasons.first())
+            .then_with(|| a.created_at.is_none().cmp(&b.created_at.is_none()))
+            .then_with(|| a.created_at.cmp(&b.created_at))
+    });
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum Visibility {
+    Public,
+    Private,
+    /// Visible to members of the enterprise (GitHub Enterprise only)
+    Internal,
+}
+
+impl Visibility {
+    /// Whether a repository with the given `gh` visibility string has this visibility.
+    pub fn matches(self, visibility: Option<&str>) -> bool {
+        let expected = match self {
+            Visibility::Public => "PUBLIC",
+            Visibility::Private => "PRIVATE",
+            Visibility::Internal => "INTERNAL",
+        };
+        visibility.is_some_and(|v| v.eq_ignore_ascii_case(expected))
+    }
+}
+
+/// Access levels to a repository, lowest first.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, ValueEnum)]
+pub enum Permission {
+    Read,
+    Triage,
+    Write,
+    Maintain,
+    Admin,
+}
+
+impl Permission {
+    /// The level of a `gh` `viewerPermission` string, if it is one.
+    pub fn from_gh(permission: &str) -> Option<Self> {
+        match permission.to_uppercase().as_str() {
+            "READ" => Some(Permission::Read),
+            "TRIAGE" => Some(Permission::Triage),
+            "WRITE" => Some(Permission::Write),
+            "MAINTAIN" => Some(Permission::Maintain),
+            "ADMIN" => Some(Permission::Admin),
+            _ => None,
+        }
+    }
+}
+
 #[derive(Debug, Deserialize)]
 pub struct GhPullRequest {
     pub number: u32,
     pub title: String,
     pub url: String,
     pub author: GhUser,
-    #[serde(rename = "reviewRequests")]
-    pub review_requests: Vec<GhUser>,
+    #[serde(rename = "reviewRequests", default)]
+    pub review_requests: Vec<GhReviewRequest>,
+    #[serde(default)]
+    pub mergeable: Option<String>,
+    #[serde(rename = "mergeStateStatus", default)]
+    pub merge_state_status: Option<String>,
+    /// Empty when the repository doesn't require reviews.
+    #[serde(rename = "reviewDecision", default)]
+    pub review_decision: Option<String>,
+    #[serde(rename = "createdAt", default)]
+    pub created_at: Option<String>,
+    #[serde(default)]
+    pub state: Option<String>,
+    #[serde(default)]
+    pub assignees: Vec<GhUser>,
+    #[serde(default)]
+    pub body: Option<String>,
+}
+
+impl GhPullRequest {
+    /// Whether GitHub reports conflicts with the base branch. Only meaningful
+    /// when `mergeable`/`mergeStateStatus` were requested.
+    pub fn has_conflicts(&self) -> bool {
+        self.mergeable.as_deref() == Some("CONFLICTING")
+            || self.merge_state_status.as_deref() == Some("DIRTY")
+    }
 }
 
 #[derive(Debug, Deserialize)]
@@ -47,6 +374,50 @@ pub struct GhUser {
     pub login: String,
 }
 
+/// A PR as reported by `gh search prs`.
+#[derive(Debug, Deserialize)]
+pub struct GhSearchPullRequest {
+    pub number: u32,
+    pub title: String,
+    pub url: String,
+    pub author: GhUser,
+    pub repository: GhSearchRepository,
+    #[serde(rename = "createdAt", default)]
+    pub created_at: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct GhSearchRepository {
+    #[serde(rename = "nameWithOwner")]
+    pub name_with_owner: String,
+}
+
+/// A requested reviewer, which is either a user or a team.
+#[derive(Debug, Deserialize, Default)]
+pub struct GhReviewRequest {
+    #[serde(rename = "__typename", default)]
+    pub typename: String,
+    #[serde(default)]
+    pub login: String,
+    #[serde(default)]
+    pub slug: String,
+}
+
+impl GhReviewRequest {
+    /// Full `org/slug` name of a requested team. `gh` reports the slug either
+    /// bare or already qualified, so the PR's org fills in when needed.
+    pub fn team_name(&self, org: &str) -> Option<String> {
+        if self.typename != "Team" || self.slug.is_empty() {
+            return None;
+        }
+        if self.slug.contains('/') {
+            Some(self.slug.clone())
+        } else {
+            Some(format!("{}/{}", org, self.slug))
+        }
+    }
+}
+
 impl Config {
     pub fn config_path() -> Result<PathBuf> {
         let config_dir =
@@ -54,7 +425,33 @@ impl Config {
         Ok(config_dir.join("review-radar").join("config.toml"))
     }
 
-    pub fn config_path_in_dir(dir: &PathBuf) -> PathBuf {
+    /// Where state kept between runs (checkpoints, history) goes, as XDG
+    /// wants it out of the config directory. Platforms without a state
+    /// directory, like macOS, use the config directory instead.
+    pub fn state_path() -> Result<PathBuf> {
+        match dirs::state_dir() {
+            Some(state_dir) => Ok(state_dir.join("review-radar")),
+            None => Ok(Self::config_dir()?),
+        }
+    }
+
+    /// Where caches that are safe to delete at any time go.
+    pub fn cache_path() -> Result<PathBuf> {
+        match dirs::cache_dir() {
+            Some(cache_dir) => Ok(cache_dir.join("review-radar")),
+            None => Ok(Self::config_dir()?),
+        }
+    }
+
+    fn config_dir() -> Result<PathBuf> {
+        let config_path = Self::config_path()?;
+        Ok(config_path
+            .parent()
+            .map(Path::to_path_buf)
+            .unwrap_or(config_path))
+    }
+
+    pub fn config_path_in_dir(dir: &Path) -> PathBuf {
         dir.join("config.toml")
     }
 
@@ -63,7 +460,7 @@ impl Config {
         Self::load_from_path(&path)
     }
 
-    pub fn load_from_path(path: &PathBuf) -> Result<Self> {
+    pub fn load_from_path(path: &Path) -> Result<Self> {
         if !path.exists() {
             return Err(anyhow::anyhow!(
                 "Configuration not found. Run 'review-radar init <orgs> <username>' to set up."
@@ -79,7 +476,7 @@ impl Config {
         self.save_to_path(&path)
     }
 
-    pub fn save_to_path(&self, path: &PathBuf) -> Result<()> {
+    pub fn save_to_path(&self, path: &Path) -> Result<()> {
         if let Some(parent) = path.parent() {
             fs::create_dir_all(parent)?;
         }
@@ -107,24 +504,866 @@ impl Config {
     }
 
     pub fn set_orgs(&mut self, orgs: Vec<String>) {
-        self.orgs = orgs;
+        self.orgs = dedup_orgs(orgs).0;
+    }
+
+    /// The `[[hosts]]` entry for `host`, added with the `@me` login if there
+    /// isn't one yet.
+    pub fn host_mut(&mut self, host: &str) -> &mut HostConfig {
+        match self.hosts.iter().position(|entry| entry.host == host) {
+            Some(pos) => &mut self.hosts[pos],
+            None => {
+                self.hosts.push(HostConfig {
+                    host: host.to_string(),
+                    orgs: Vec::new(),
+                    username: "@me".to_string(),
+                });
+                self.hosts.last_mut().unwrap()
+            }
+        }
     }
 
     pub fn set_repo_pattern(&mut self, pattern: Option<String>) -> Result<()> {
-        if let Some(ref p) = pattern {
-            if p.to_lowercase() == "none" {
-                self.repo_pattern = None;
-            } else {
-                // Validate the regex
-                Regex::new(p)
-                    .map_err(|e| anyhow::anyhow!("Invalid regex pattern '{}': {}", p, e))?;
-                self.repo_pattern = pattern;
+        self.set_repo_patterns(pattern.into_iter().collect())
+    }
+
+    /// Replaces the repository filter: a single pattern is kept in
+    /// `repo_pattern`, several in `repo_patterns`. No patterns, or just
+    /// `none`, clears it.
+    pub fn set_repo_patterns(&mut self, mut patterns: Vec<String>) -> Result<()> {
+        if let [only] = patterns.as_slice() {
+            if only.to_lowercase() == "none" {
+                patterns.clear();
             }
+        }
+        for pattern in &patterns {
+            compile_repo_pattern(pattern)?;
+        }
+        if patterns.len() == 1 {
+            self.repo_pattern = patterns.pop();
+            self.repo_patterns.clear();
         } else {
-            self.repo_pattern = pattern;
+            self.repo_pattern = None;
+            self.repo_patterns = patterns;
+        }
+        Ok(())
+    }
+
+    /// Replaces `exclude_repos` with `globs` if they are all valid; a single
+    /// `none` clears the list.
+    pub fn set_exclude_repos(&mut self, mut globs: Vec<String>) -> Result<()> {
+        if let [only] = globs.as_slice() {
+            if only.to_lowercase() == "none" {
+                globs.clear();
+            }
+        }
+        for glob in &globs {
+            RepoGlob::new(glob)?;
+        }
+        self.exclude_repos = globs;
+        Ok(())
+    }
+
+    /// The compiled `exclude_repos`, failing on the first invalid entry.
+    pub fn exclude_repo_globs(&self) -> Result<Vec<RepoGlob>> {
+        self.exclude_repos
+            .iter()
+            .map(|glob| RepoGlob::new(glob))
+            .collect()
+    }
+
+    /// The repository filter as one regex, matching if `repo_pattern` or
+    /// any of `repo_patterns` does.
+    pub fn effective_repo_pattern(&self) -> Option<String> {
+        let patterns: Vec<&str> = self
+            .repo_pattern
+            .iter()
+            .chain(&self.repo_patterns)
+            .map(String::as_str)
+            .collect();
+        combine_repo_patterns(&patterns)
+    }
+}
+
+/// One regex matching whatever any of `patterns` matches, or `None` when
+/// there are none. Each pattern is grouped so its own `|` and anchors stay
+/// its own.
+pub fn combine_repo_patterns(patterns: &[&str]) -> Option<String> {
+    match patterns {
+        [] => None,
+        [only] => Some(only.to_string()),
+        _ => Some(
+            patterns
+                .iter()
+                .map(|pattern| format!("(?:{})", pattern))
+                .collect::<Vec<_>>()
+                .join("|"),
+        ),
+    }
+}
+
+/// The organizations among `memberships` that `prefix` stands for, ignoring
+/// case. One match is used whatever follows the prefix; several must all
+/// continue with a separator (`acme` for `acme-eng` and `acme-infra`), or
+/// the prefix must end with one. A mix like `acme-eng` and `acmecorp` is
+/// more likely a typo than a family.
+pub fn expand_org_prefix(prefix: &str, memberships: &[String]) -> Result<Vec<String>> {
+    let prefix_lower = prefix.to_lowercase();
+    let matches: Vec<String> = memberships
+        .iter()
+        .filter(|org| org.to_lowercase().starts_with(&prefix_lower))
+        .cloned()
+        .collect();
+    let is_separator = |c: char| matches!(c, '-' | '_' | '.');
+    let family = prefix.ends_with(is_separator)
+        || matches.iter().all(|org| {
+            org.get(prefix.len()..)
+                .and_then(|rest| rest.chars().next())
+                .is_some_and(is_separator)
+        });
+    match matches.len() {
+        0 => Err(anyhow::anyhow!(
+            "'{}' is not an organization, and none of yours start with it",
+            prefix
+        )),
+        1 => Ok(matches),
+        _ if family => Ok(matches),
+        _ => Err(anyhow::anyhow!(
+            "'{}' is not an organization and could mean any of {}: name the ones you want",
+            prefix,
+            matches.join(", ")
+        )),
+    }
+}
+
+/// Splits a `--project` value, `OWNER/NUMBER` or just `NUMBER` for a project
+/// of `default_owner`, into its owner and number.
+pub fn parse_project(spec: &str, default_owner: Option<&str>) -> Result<(String, u32)> {
+    let (owner, number) = match spec.rsplit_once('/') {
+        Some((owner, number)) if !owner.is_empty() => (owner.to_string(), number),
+        Some(_) => {
+            return Err(anyhow::anyhow!(
+                "'{}' has no project owner before the '/'",
+                spec
+            ))
+        }
+        None => {
+            let owner = default_owner.ok_or_else(|| {
+                anyhow::anyhow!(
+                    "Name the owner of project {0}, e.g. --project my-org/{0} (or @me/{0} for your own)",
+                    spec
+                )
+            })?;
+            (owner.to_string(), spec)
+        }
+    };
+    let number = number
+        .parse()
+        .map_err(|_| anyhow::anyhow!("'{}' is not a project number", number))?;
+    Ok((owner, number))
+}
+
+/// `orgs` with repeats removed, keeping the first occurrence and the order,
+/// plus the repeats that were dropped. Org names are compared ignoring case,
+/// as GitHub does.
+pub fn dedup_orgs(orgs: Vec<String>) -> (Vec<String>, Vec<String>) {
+    let mut kept: Vec<String> = Vec::new();
+    let mut duplicates = Vec::new();
+    for org in orgs {
+        if kept.iter().any(|seen| seen.eq_ignore_ascii_case(&org)) {
+            duplicates.push(org);
+        } else {
+            kept.push(org);
+        }
+    }
+    (kept, duplicates)
+}
+
+/// Compiles a repository name pattern, with the error message users see for
+/// invalid ones.
+pub fn compile_repo_pattern(pattern: &str) -> Result<Regex> {
+    Regex::new(pattern).map_err(|e| anyhow::anyhow!("Invalid regex pattern '{}': {}", pattern, e))
+}
+
+/// An `exclude_repos` entry: `*` stands for any run of characters and `?`
+/// for one, within a name. A glob with a `/`, like `acme/*-test`, matches
+/// `org/repo`; one without, like `*-sandbox`, the repository name in any org.
+/// Names are compared ignoring case, as GitHub does.
+#[derive(Debug, Clone)]
+pub struct RepoGlob {
+    glob: String,
+    regex: Regex,
+}
+
+impl RepoGlob {
+    pub fn new(glob: &str) -> Result<Self> {
+        let parts: Vec<&str> = glob.split('/').collect();
+        if parts.len() > 2 || parts.iter().any(|part| part.is_empty()) {
+            return Err(anyhow::anyhow!(
+                "Invalid repository glob '{}': use NAME or ORG/NAME, e.g. '*-sandbox' or 'acme/*-test'",
+                glob
+            ));
+        }
+        let mut regex = String::from("(?i)^");
+        for c in glob.chars() {
+            match c {
+                '*' => regex.push_str("[^/]*"),
+                '?' => regex.push_str("[^/]"),
+                c => regex.push_str(&regex::escape(&c.to_string())),
+            }
+        }
+        regex.push('$');
+        Ok(Self {
+            glob: glob.to_string(),
+            regex: Regex::new(&regex)?,
+        })
+    }
+
+    pub fn as_str(&self) -> &str {
+        &self.glob
+    }
+
+    pub fn matches(&self, org: &str, name: &str) -> bool {
+        if self.glob.contains('/') {
+            self.regex.is_match(&format!("{}/{}", org, name))
+        } else {
+            self.regex.is_match(name)
+        }
+    }
+}
+
+/// Optional `gh pr list` fields, requested only when something uses them.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct PrFields {
+    /// `mergeable` and `mergeStateStatus`, for conflict detection.
+    pub merge_state: bool,
+    /// `reviewDecision`, for grouping by review state.
+    pub review_decision: bool,
+    /// `createdAt`, for PR ages.
+    pub created_at: bool,
+    /// `state`, for telling closed PRs apart when closed ones are listed too.
+    pub state: bool,
+    /// Every field in [`RAW_GH_FIELDS`], kept unparsed for `--format raw-gh`.
+    pub raw: bool,
+    /// `assignees` and `body`, for the inbox's assignments and mentions.
+    pub inbox: bool,
+}
+
+/// Fields requested for `--format raw-gh`: everything `gh pr list` offers
+/// except the nested connections (commits, files, comments, reviews, checks,
+/// projects) that make GitHub's query too costly for hundreds of PRs.
+pub const RAW_GH_FIELDS: &str = "additions,assignees,author,autoMergeRequest,baseRefName,body,changedFiles,closed,closedAt,createdAt,deletions,headRefName,headRefOid,headRepository,headRepositoryOwner,id,isCrossRepository,isDraft,labels,maintainerCanModify,mergeCommit,mergeStateStatus,mergeable,mergedAt,mergedBy,milestone,number,reviewDecision,reviewRequests,state,title,updatedAt,url";
+
+/// JSON fields to request from `gh pr list`, trimmed to what the active mode uses.
+pub fn pr_json_fields(own_prs: bool, extra: PrFields) -> String {
+    if extra.raw {
+        return RAW_GH_FIELDS.to_string();
+    }
+    let mut fields = vec!["number", "title", "url", "author"];
+    // Own PRs are already filtered by `--author`, so review requests are unused
+    if !own_prs {
+        fields.push("reviewRequests");
+    }
+    if extra.merge_state {
+        fields.extend(["mergeable", "mergeStateStatus"]);
+    }
+    if extra.review_decision {
+        fields.push("reviewDecision");
+    }
+    if extra.created_at {
+        fields.push("createdAt");
+    }
+    if extra.state {
+        fields.push("state");
+    }
+    if extra.inbox {
+        fields.extend(["assignees", "body"]);
+    }
+    fields.join(",")
+}
+
+/// Parses a GitHub timestamp (`2024-01-15T10:30:00Z`) into Unix seconds.
+pub fn parse_timestamp(timestamp: &str) -> Option<u64> {
+    let (date, time) = timestamp.strip_suffix('Z')?.split_once('T')?;
+    let mut date = date.splitn(3, '-').map(|part| part.parse::<i64>().ok());
+    let (year, month, day) = (date.next()??, date.next()??, date.next()??);
+    let mut time = time.splitn(3, ':').map(|part| part.parse::<u64>().ok());
+    let (hour, minute, second) = (time.next()??, time.next()??, time.next()??);
+    if !(1..=12).contains(&month) || !(1..=31).contains(&day) {
+        return None;
+    }
+
+    // Days since the epoch for a proleptic Gregorian date (Howard Hinnant's days_from_civil)
+    let year = if month <= 2 { year - 1 } else { year };
+    let era = year.div_euclid(400);
+    let yoe = year.rem_euclid(400);
+    let mp = (month + 9) % 12;
+    let doy = (153 * mp + 2) / 5 + day - 1;
+    let doe = yoe * 365 + yoe / 4 - yoe / 100 + doy;
+    let days = u64::try_from(era * 146_097 + doe - 719_468).ok()?;
+    Some(days * 86_400 + hour * 3_600 + minute * 60 + second)
+}
+
+/// Parses a date given on the command line, `2024-01-15` (midnight UTC) or a
+/// full GitHub timestamp, into Unix seconds.
+pub fn parse_date(input: &str) -> Result<u64> {
+    parse_timestamp(input)
+        .or_else(|| parse_timestamp(&format!("{}T00:00:00Z", input)))
+        .ok_or_else(|| anyhow::anyhow!("'{}' is not a date: use YYYY-MM-DD", input))
+}
+
+/// The UTC date (`2024-01-15`) of a Unix timestamp.
+pub fn format_date(timestamp: u64) -> String {
+    // Howard Hinnant's civil_from_days, the inverse of the above
+    let z = timestamp / 86_400 + 719_468;
+    let era = z / 146_097;
+    let doe = z % 146_097;
+    let yoe = (doe - doe / 1_460 + doe / 36_524 - doe / 146_096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = doy - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    let year = era * 400 + yoe + u64::from(month <= 2);
+    format!("{:04}-{:02}-{:02}", year, month, day)
+}
+
+/// A GitHub-style UTC timestamp (`2024-01-15T10:30:00Z`), the inverse of [`parse_timestamp`].
+pub fn format_timestamp(timestamp: u64) -> String {
+    let seconds = timestamp % 86_400;
+    format!(
+        "{}T{:02}:{:02}:{:02}Z",
+        format_date(timestamp),
+        seconds / 3_600,
+        seconds / 60 % 60,
+        seconds % 60
+    )
+}
+
+/// Reads a GitHub token from a file, e.g. a mounted secret, ignoring
+/// surrounding whitespace.
+pub fn read_token_file(path: &Path) -> Result<String> {
+    let content = fs::read_to_string(path)
+        .map_err(|e| anyhow::anyhow!("Could not read token file '{}': {}", path.display(), e))?;
+    let token = content.trim();
+    if token.is_empty() {
+        return Err(anyhow::anyhow!("Token file '{}' is empty", path.display()));
+    }
+    Ok(token.to_string())
+}
+
+/// Replaces `path` with `contents` via a temporary file in the same directory
+/// and a rename, so readers see either the old or the new contents, never a
+/// partial write.
+pub fn write_atomically(path: &Path, contents: &str) -> Result<()> {
+    let name = path
+        .file_name()
+        .ok_or_else(|| anyhow::anyhow!("'{}' is not a file path", path.display()))?;
+    let mut temp_name = name.to_os_string();
+    temp_name.push(format!(".{}.tmp", std::process::id()));
+    let temp_path = path.with_file_name(temp_name);
+    fs::write(&temp_path, contents)
+        .and_then(|_| fs::rename(&temp_path, path))
+        .map_err(|e| {
+            let _ = fs::remove_file(&temp_path);
+            anyhow::anyhow!("Could not write '{}': {}", path.display(), e)
+        })
+}
+
+/// Whether `gh` stderr output points to a connectivity problem rather than
+/// a missing or invalid login.
+pub fn is_network_error(stderr: &str) -> bool {
+    const HINTS: [&str; 8] = [
+        "error connecting to",
+        "could not resolve host",
+        "no such host",
+        "connection refused",
+        "connection reset",
+        "network is unreachable",
+        "timeout",
+        "timed out",
+    ];
+    let stderr = stderr.to_lowercase();
+    HINTS.iter().any(|hint| stderr.contains(hint))
+}
+
+/// Picks `count` items pseudo-randomly from `items`, keeping their original order.
+/// The same seed always picks the same items.
+pub fn sample<T>(items: Vec<T>, count: usize, seed: u64) -> Vec<T> {
+    if count >= items.len() {
+        return items;
+    }
+
+    // splitmix64: tiny, well-distributed and good enough for picking repos
+    let mut state = seed;
+    let mut next = move || {
+        state = state.wrapping_add(0x9e37_79b9_7f4a_7c15);
+        let mut z = state;
+        z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+        z ^ (z >> 31)
+    };
+
+    // Partial Fisher-Yates shuffle of the indices
+    let mut indices: Vec<usize> = (0..items.len()).collect();
+    for i in 0..count {
+        let j = i + (next() % (items.len() - i) as u64) as usize;
+        indices.swap(i, j);
+    }
+    let mut picked = indices[..count].to_vec();
+    picked.sort_unstable();
+
+    let mut picked = picked.into_iter().peekable();
+    items
+        .into_iter()
+        .enumerate()
+        .filter(|(i, _)| {
+            if picked.peek() == Some(i) {
+                picked.next();
+                true
+            } else {
+                false
+            }
+        })
+        .map(|(_, item)| item)
+        .collect()
+}
+
+/// Applies `f` to every item on up to `workers` threads and returns the results
+/// in the order of `items`, however the threads happen to finish.
+pub fn parallel_map<T, R, F>(items: Vec<T>, workers: usize, f: F) -> Vec<R>
+where
+    T: Send,
+    R: Send,
+    F: Fn(T) -> R + Sync,
+{
+    let mut results = Vec::with_capacity(items.len());
+    let Ok(()) = parallel_each(items, workers, f, |index, result| {
+        results.push((index, result));
+        Ok::<_, std::convert::Infallible>(())
+    });
+    results.sort_unstable_by_key(|(index, _)| *index);
+    results.into_iter().map(|(_, result)| result).collect()
+}
+
+/// Applies `f` to every item on up to `workers` threads, handing each result
+/// with the item's index to `handle` on the calling thread as soon as it's
+/// ready, so `handle` needn't be thread-safe. Once `handle` fails no more
+/// items are started, and its error is returned.
+pub fn parallel_each<T, R, E, F, H>(
+    items: Vec<T>,
+    workers: usize,
+    f: F,
+    mut handle: H,
+) -> Result<(), E>
+where
+    T: Send,
+    R: Send,
+    F: Fn(T) -> R + Sync,
+    H: FnMut(usize, R) -> Result<(), E>,
+{
+    let workers = workers.clamp(1, items.len().max(1));
+    let queue = Mutex::new(items.into_iter().enumerate());
+
+    thread::scope(|scope| {
+        let (tx, rx) = mpsc::channel();
+        let (queue, f) = (&queue, &f);
+        for _ in 0..workers {
+            let tx = tx.clone();
+            scope.spawn(move || loop {
+                let Some((index, item)) = queue.lock().unwrap().next() else {
+                    break;
+                };
+                // Tag each result with its position; the receiver is gone once
+                // `handle` failed
+                if tx.send((index, f(item))).is_err() {
+                    break;
+                }
+            });
+        }
+        drop(tx);
+        rx.into_iter()
+            .try_for_each(|(index, result)| handle(index, result))
+    })
+}
+
+/// Progress of an in-flight scan, persisted every couple of seconds and once
+/// more if the scan fails, so an interrupted run can be picked up again with
+/// `--resume`.
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Default)]
+pub struct Checkpoint {
+    /// Identifies the search (mode, user, orgs, pattern) the checkpoint belongs to.
+    pub key: String,
+    /// Repositories already checked, as `org/repo`.
+    pub completed: Vec<String>,
+    /// Matching PRs found in the completed repositories.
+    pub prs: Vec<PullRequest>,
+}
+
+impl Checkpoint {
+    pub fn new(key: String) -> Self {
+        Self {
+            key,
+            ..Default::default()
+        }
+    }
+
+    /// Each host gets its own checkpoint so a multi-host run can resume on any of them.
+    pub fn path(host: Option<&str>) -> Result<PathBuf> {
+        let file_name = match host {
+            Some(host) => format!("checkpoint-{}.json", host),
+            None => "checkpoint.json".to_string(),
+        };
+        Ok(Config::state_path()?.join(file_name))
+    }
+
+    /// Loads the checkpoint at `path`, returning `None` if there isn't one.
+    pub fn load_from_path(path: &Path) -> Result<Option<Self>> {
+        if !path.exists() {
+            return Ok(None);
+        }
+        let content = fs::read_to_string(path)?;
+        Ok(Some(serde_json::from_str(&content)?))
+    }
+
+    pub fn save_to_path(&self, path: &Path) -> Result<()> {
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        fs::write(path, serde_json::to_string(self)?)?;
+        Ok(())
+    }
+
+    pub fn clear_at_path(path: &Path) -> Result<()> {
+        if path.exists() {
+            fs::remove_file(path)?;
+        }
+        Ok(())
+    }
+}
+
+/// A repository a scan couldn't check, with where and for whom it was scanned.
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct FailedRepo {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub host: Option<String>,
+    /// As `org/repo`.
+    pub repo: String,
+    pub username: String,
+}
+
+/// The results of the last scan that couldn't check every repository, kept
+/// so `rr retry-failed` can check just those again and merge the results.
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Default)]
+pub struct LastScan {
+    pub mode: SearchMode,
+    pub prs: Vec<PullRequest>,
+    pub failed: Vec<FailedRepo>,
+}
+
+impl LastScan {
+    pub fn path() -> Result<PathBuf> {
+        Ok(Config::state_path()?.join("last-scan.json"))
+    }
+
+    /// Loads the scan at `path`, returning `None` if there isn't one.
+    pub fn load_from_path(path: &Path) -> Result<Option<Self>> {
+        if !path.exists() {
+            return Ok(None);
+        }
+        let content = fs::read_to_string(path)?;
+        Ok(Some(serde_json::from_str(&content)?))
+    }
+
+    pub fn save_to_path(&self, path: &Path) -> Result<()> {
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        fs::write(path, serde_json::to_string(self)?)?;
+        Ok(())
+    }
+
+    pub fn clear_at_path(path: &Path) -> Result<()> {
+        if path.exists() {
+            fs::remove_file(path)?;
+        }
+        Ok(())
+    }
+
+    /// This scan's PRs followed by those of `found`, a retry of its failed
+    /// repositories, that it didn't have yet.
+    pub fn with_retried(self, found: Vec<PullRequest>) -> Vec<PullRequest> {
+        let mut prs = self.prs;
+        for pr in found {
+            if !prs.iter().any(|known| known.html_url == pr.html_url) {
+                prs.push(pr);
+            }
+        }
+        prs
+    }
+}
+
+/// How long a resolved `@me` login is trusted before asking GitHub again.
+pub const IDENTITY_TTL_SECS: u64 = 7 * 24 * 60 * 60;
+
+/// The authenticated login `@me` resolved to, cached to skip a `gh api user`
+/// round trip on every run.
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct CachedIdentity {
+    pub login: String,
+    /// Unix time the login was fetched.
+    pub fetched_at: u64,
+    /// Modification time of gh's auth file when fetched, to notice `gh auth` changes.
+    pub auth_stamp: Option<u64>,
+}
+
+impl CachedIdentity {
+    pub fn path(host: Option<&str>) -> Result<PathBuf> {
+        let file_name = match host {
+            Some(host) => format!("identity-{}.json", host),
+            None => "identity.json".to_string(),
+        };
+        Ok(Config::cache_path()?.join(file_name))
+    }
+
+    pub fn load_from_path(path: &Path) -> Result<Option<Self>> {
+        if !path.exists() {
+            return Ok(None);
+        }
+        let content = fs::read_to_string(path)?;
+        Ok(serde_json::from_str(&content).ok())
+    }
+
+    pub fn save_to_path(&self, path: &Path) -> Result<()> {
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        fs::write(path, serde_json::to_string(self)?)?;
+        Ok(())
+    }
+
+    /// Whether the cached login can still be used at time `now`, given gh's current auth stamp.
+    pub fn is_fresh(&self, now: u64, auth_stamp: Option<u64>) -> bool {
+        now.saturating_sub(self.fetched_at) < IDENTITY_TTL_SECS && self.auth_stamp == auth_stamp
+    }
+}
+
+/// How long a cached repository list is used before listing the org again.
+pub const REPO_CACHE_TTL_SECS: u64 = 60 * 60;
+
+/// Each org's full, unfiltered repository list. Filters like `--repo-pattern`
+/// are applied after reading it, so changing them never needs a refetch.
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Default)]
+pub struct RepoCache {
+    pub orgs: BTreeMap<String, CachedRepos>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct CachedRepos {
+    /// Unix time the list was fetched.
+    pub fetched_at: u64,
+    pub repos: Vec<GhRepo>,
+}
+
+impl RepoCache {
+    pub fn path(host: Option<&str>) -> Result<PathBuf> {
+        let file_name = match host {
+            Some(host) => format!("repo-cache-{}.json", host),
+            None => "repo-cache.json".to_string(),
+        };
+        Ok(Config::cache_path()?.join(file_name))
+    }
+
+    /// Loads the cache at `path`; a missing or unreadable cache is just empty.
+    pub fn load_from_path(path: &Path) -> Self {
+        fs::read_to_string(path)
+            .ok()
+            .and_then(|content| serde_json::from_str(&content).ok())
+            .unwrap_or_default()
+    }
+
+    pub fn save_to_path(&self, path: &Path) -> Result<()> {
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)?;
         }
+        fs::write(path, serde_json::to_string(self)?)?;
         Ok(())
     }
+
+    /// The org's repositories, unless they were never cached or are older than the TTL.
+    pub fn fresh(&self, org: &str, now: u64) -> Option<&[GhRepo]> {
+        self.orgs
+            .get(org)
+            .filter(|cached| now.saturating_sub(cached.fetched_at) < REPO_CACHE_TTL_SECS)
+            .map(|cached| cached.repos.as_slice())
+    }
+
+    pub fn insert(&mut self, org: &str, repos: Vec<GhRepo>, now: u64) {
+        self.orgs.insert(
+            org.to_string(),
+            CachedRepos {
+                fetched_at: now,
+                repos,
+            },
+        );
+    }
+}
+
+/// Logins that have had a review request matched at least once. A login
+/// that never has, after scanning plenty of repositories, is likely a typo.
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Default)]
+pub struct MatchHistory {
+    pub matched: BTreeSet<String>,
+}
+
+impl MatchHistory {
+    pub fn path() -> Result<PathBuf> {
+        Ok(Config::state_path()?.join("matched-logins.json"))
+    }
+
+    /// Loads the list at `path`; a missing or unreadable one is just empty.
+    pub fn load_from_path(path: &Path) -> Self {
+        fs::read_to_string(path)
+            .ok()
+            .and_then(|content| serde_json::from_str(&content).ok())
+            .unwrap_or_default()
+    }
+
+    pub fn save_to_path(&self, path: &Path) -> Result<()> {
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        fs::write(path, serde_json::to_string(self)?)?;
+        Ok(())
+    }
+}
+
+/// One recorded scan of the review queue: when it ran and the PRs awaiting
+/// review then.
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Default)]
+pub struct HistoryEntry {
+    /// Unix time of the scan.
+    pub at: u64,
+    pub prs: Vec<HistoryPr>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Default)]
+pub struct HistoryPr {
+    pub url: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub created_at: Option<String>,
+}
+
+/// The review queue over time, as JSON lines of [`HistoryEntry`] appended by
+/// `--record-history` and read by `rr stats`.
+pub struct History;
+
+impl History {
+    pub fn path() -> Result<PathBuf> {
+        Ok(Config::state_path()?.join("history.jsonl"))
+    }
+
+    /// Adds `entry` at the end of the history at `path`, creating it if needed.
+    pub fn append_to_path(path: &Path, entry: &HistoryEntry) -> Result<()> {
+        use std::io::Write;
+
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        let mut file = fs::OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(path)?;
+        writeln!(file, "{}", serde_json::to_string(entry)?)?;
+        Ok(())
+    }
+
+    /// Every entry at `path`, oldest first; no history is an empty one.
+    pub fn load_from_path(path: &Path) -> Result<Vec<HistoryEntry>> {
+        if !path.exists() {
+            return Ok(Vec::new());
+        }
+        let content = fs::read_to_string(path)?;
+        let mut entries = content
+            .lines()
+            .enumerate()
+            .filter(|(_, line)| !line.trim().is_empty())
+            .map(|(i, line)| {
+                serde_json::from_str(line).map_err(|e| {
+                    anyhow::anyhow!("Bad entry on line {} of '{}': {}", i + 1, path.display(), e)
+                })
+            })
+            .collect::<Result<Vec<HistoryEntry>>>()?;
+        entries.sort_by_key(|entry| entry.at);
+        Ok(entries)
+    }
+}
+
+/// Review throughput worked out from the recorded history.
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct HistoryStats {
+    pub runs: usize,
+    /// Times of the first and last recorded scan.
+    pub first_at: u64,
+    pub last_at: u64,
+    /// PRs awaiting review in the last scan, and how long the oldest has
+    /// waited since it was opened.
+    pub backlog: usize,
+    pub oldest_backlog_days: Option<u64>,
+    /// PRs that were in the queue and later weren't, i.e. got reviewed (or
+    /// had the request withdrawn).
+    pub reviewed: usize,
+    /// Average time from a PR first being seen in the queue to the scan that
+    /// no longer found it.
+    pub average_review_secs: Option<u64>,
+    /// PRs leaving the queue per week, keyed by the Monday starting it.
+    pub reviewed_per_week: BTreeMap<String, usize>,
+}
+
+/// Aggregates `entries` (oldest first) into [`HistoryStats`], or `None` when
+/// nothing was recorded. Reviews can only be timed to the scan that noticed
+/// them, so the times are as precise as the scans are frequent.
+pub fn history_stats(entries: &[HistoryEntry]) -> Option<HistoryStats> {
+    let (first, last) = (entries.first()?, entries.last()?);
+    let mut first_seen: BTreeMap<&str, u64> = BTreeMap::new();
+    let mut review_secs = Vec::new();
+    let mut reviewed_per_week = BTreeMap::new();
+    let mut previous: BTreeSet<&str> = BTreeSet::new();
+    for entry in entries {
+        let current: BTreeSet<&str> = entry.prs.iter().map(|pr| pr.url.as_str()).collect();
+        for url in &current {
+            first_seen.entry(url).or_insert(entry.at);
+        }
+        for url in previous.difference(&current) {
+            // A PR can come back with a new request, which starts a new wait
+            if let Some(seen) = first_seen.remove(url) {
+                review_secs.push(entry.at.saturating_sub(seen));
+            }
+            let day = entry.at / 86_400;
+            // 1970-01-01 was a Thursday, three days after a Monday
+            let monday = (day - (day + 3) % 7) * 86_400;
+            *reviewed_per_week.entry(format_date(monday)).or_insert(0) += 1;
+        }
+        previous = current;
+    }
+
+    let average_review_secs = (!review_secs.is_empty())
+        .then(|| review_secs.iter().sum::<u64>() / review_secs.len() as u64);
+    let oldest_backlog_days = last
+        .prs
+        .iter()
+        .filter_map(|pr| parse_timestamp(pr.created_at.as_deref()?))
+        .min()
+        .map(|created| last.at.saturating_sub(created) / 86_400);
+    Some(HistoryStats {
+        runs: entries.len(),
+        first_at: first.at,
+        last_at: last.at,
+        backlog: last.prs.len(),
+        oldest_backlog_days,
+        reviewed: review_secs.len(),
+        average_review_secs,
+        reviewed_per_week,
+    })
 }
 
 pub fn parse_org_modification(org_str: &str) -> OrgModification {
@@ -138,6 +1377,34 @@ pub fn parse_org_modification(org_str: &str) -> OrgModification {
     }
 }
 
+/// Parses a selection such as `1,3-5` of items numbered from 1 to `count`
+/// into zero-based indices, in ascending order without duplicates.
+pub fn parse_selection(input: &str, count: usize) -> Result<Vec<usize>> {
+    let mut selected = BTreeSet::new();
+    for part in input
+        .split(',')
+        .map(str::trim)
+        .filter(|part| !part.is_empty())
+    {
+        let (start, end) = part.split_once('-').unwrap_or((part, part));
+        let parse = |n: &str| {
+            n.trim()
+                .parse::<usize>()
+                .ok()
+                .filter(|n| (1..=count).contains(n))
+                .ok_or_else(|| {
+                    anyhow::anyhow!("'{}' is not a number between 1 and {}", n.trim(), count)
+                })
+        };
+        let (start, end) = (parse(start)?, parse(end)?);
+        if start > end {
+            return Err(anyhow::anyhow!("'{}' is not an ascending range", part));
+        }
+        selected.extend(start - 1..end);
+    }
+    Ok(selected.into_iter().collect())
+}
+
 #[derive(Debug, PartialEq)]
 pub enum OrgModification {
     Add(String),
@@ -156,6 +1423,7 @@ mod tests {
             orgs: vec!["org1".to_string(), "org2".to_string()],
             username: "testuser".to_string(),
             repo_pattern: Some("test-.*".to_string()),
+            ..Default::default()
         };
 
         assert_eq!(config.orgs.len(), 2);
@@ -169,6 +1437,7 @@ mod tests {
             orgs: vec!["org1".to_string()],
             username: "testuser".to_string(),
             repo_pattern: None,
+            ..Default::default()
         };
 
         let toml_str = toml::to_string(&config).unwrap();
@@ -177,15 +1446,132 @@ mod tests {
         assert_eq!(config, deserialized);
     }
 
+    #[test]
+    fn test_config_with_hosts_serialization() {
+        let config = Config {
+            orgs: vec!["org1".to_string()],
+            username: "testuser".to_string(),
+            hosts: vec![HostConfig {
+                host: "github.example.com".to_string(),
+                orgs: vec!["internal".to_string()],
+                username: "corp-user".to_string(),
+            }],
+            ..Default::default()
+        };
+
+        let toml_str = toml::to_string_pretty(&config).unwrap();
+        assert!(toml_str.contains("[[hosts]]"));
+        let deserialized: Config = toml::from_str(&toml_str).unwrap();
+        assert_eq!(config, deserialized);
+
+        // Configs without hosts still load
+        let legacy: Config = toml::from_str("orgs = [\"org1\"]\nusername = \"me\"\n").unwrap();
+        assert!(legacy.hosts.is_empty());
+    }
+
+    #[test]
+    fn test_config_defaults_section() {
+        let config: Config = toml::from_str(
+            r#"
+            orgs = ["org1"]
+            username = "me"
+
+            [defaults]
+            format = "count-by-repo"
+            no_pager = true
+            sort = "age"
+            concurrency = 4
+            "#,
+        )
+        .unwrap();
+        assert_eq!(config.defaults.format, Some(OutputFormat::CountByRepo));
+        assert!(config.defaults.no_pager);
+        assert_eq!(config.defaults.sort, Some(SortKey::Age));
+        assert_eq!(config.defaults.concurrency, Some(4));
+
+        let toml_str = toml::to_string_pretty(&config).unwrap();
+        assert_eq!(toml::from_str::<Config>(&toml_str).unwrap(), config);
+
+        // An empty section isn't written out
+        let plain = Config {
+            orgs: vec!["org1".to_string()],
+            username: "me".to_string(),
+            ..Default::default()
+        };
+        assert!(!toml::to_string_pretty(&plain).unwrap().contains("defaults"));
+    }
+
+    #[test]
+    fn test_config_org_usernames_section() {
+        let config: Config = toml::from_str(
+            r#"
+            orgs = ["org1", "org2"]
+            username = "me"
+
+            [org_usernames]
+            org2 = "me-alias"
+            "#,
+        )
+        .unwrap();
+        assert_eq!(config.org_usernames.get("org2").unwrap(), "me-alias");
+        assert!(!config.org_usernames.contains_key("org1"));
+
+        let toml_str = toml::to_string_pretty(&config).unwrap();
+        assert_eq!(toml::from_str::<Config>(&toml_str).unwrap(), config);
+    }
+
+    #[test]
+    fn test_config_org_names_section() {
+        let config: Config = toml::from_str(
+            r#"
+            orgs = ["acme-eng-internal-2"]
+            username = "me"
+
+            [org_names]
+            acme-eng-internal-2 = "Acme Engineering"
+            "#,
+        )
+        .unwrap();
+        assert_eq!(
+            config.org_names.get("acme-eng-internal-2").unwrap(),
+            "Acme Engineering"
+        );
+
+        let toml_str = toml::to_string_pretty(&config).unwrap();
+        assert_eq!(toml::from_str::<Config>(&toml_str).unwrap(), config);
+        assert!(!toml::to_string_pretty(&Config::default())
+            .unwrap()
+            .contains("org_names"));
+    }
+
+    #[test]
+    fn test_config_pinned_repos_section() {
+        let config: Config = toml::from_str(
+            r#"
+            orgs = ["org1"]
+            username = "me"
+
+            [repos]
+            org1 = ["api", "web"]
+            "#,
+        )
+        .unwrap();
+        assert_eq!(config.repos["org1"], ["api", "web"]);
+
+        let toml_str = toml::to_string_pretty(&config).unwrap();
+        assert_eq!(toml::from_str::<Config>(&toml_str).unwrap(), config);
+    }
+
     #[test]
     fn test_config_save_and_load() {
         let temp_dir = TempDir::new().unwrap();
-        let config_path = Config::config_path_in_dir(&temp_dir.path().to_path_buf());
+        let config_path = Config::config_path_in_dir(temp_dir.path());
 
         let config = Config {
             orgs: vec!["test-org".to_string()],
             username: "testuser".to_string(),
             repo_pattern: Some("backend-.*".to_string()),
+            ..Default::default()
         };
 
         // Save config
@@ -212,6 +1598,7 @@ mod tests {
             orgs: vec!["org1".to_string()],
             username: "testuser".to_string(),
             repo_pattern: None,
+            ..Default::default()
         };
 
         // Add new org
@@ -230,6 +1617,7 @@ mod tests {
             orgs: vec!["org1".to_string(), "org2".to_string()],
             username: "testuser".to_string(),
             repo_pattern: None,
+            ..Default::default()
         };
 
         // Remove existing org
@@ -248,6 +1636,7 @@ mod tests {
             orgs: vec!["org1".to_string()],
             username: "testuser".to_string(),
             repo_pattern: None,
+            ..Default::default()
         };
 
         let new_orgs = vec!["new1".to_string(), "new2".to_string(), "new3".to_string()];
@@ -262,6 +1651,7 @@ mod tests {
             orgs: vec!["org1".to_string()],
             username: "testuser".to_string(),
             repo_pattern: None,
+            ..Default::default()
         };
 
         // Set valid pattern
@@ -279,6 +1669,715 @@ mod tests {
         assert!(result.is_err());
     }
 
+    #[test]
+    fn test_set_repo_patterns() {
+        let mut config = Config::default();
+        let patterns = vec!["backend-.*".to_string(), "^api-".to_string()];
+        config.set_repo_patterns(patterns.clone()).unwrap();
+        assert_eq!(config.repo_pattern, None);
+        assert_eq!(config.repo_patterns, patterns);
+
+        // A single pattern keeps the plain string form
+        config.set_repo_patterns(vec!["web".to_string()]).unwrap();
+        assert_eq!(config.repo_pattern.as_deref(), Some("web"));
+        assert!(config.repo_patterns.is_empty());
+
+        assert!(config
+            .set_repo_patterns(vec!["ok".to_string(), "[invalid".to_string()])
+            .is_err());
+        assert_eq!(config.repo_pattern.as_deref(), Some("web"));
+
+        config.set_repo_patterns(patterns).unwrap();
+        config.set_repo_patterns(vec!["none".to_string()]).unwrap();
+        assert_eq!(config, Config::default());
+    }
+
+    #[test]
+    fn test_repo_glob() {
+        let sandbox = RepoGlob::new("*-sandbox").unwrap();
+        assert!(sandbox.matches("acme", "api-sandbox"));
+        assert!(sandbox.matches("other", "Web-Sandbox"));
+        assert!(!sandbox.matches("acme", "api-sandbox-2"));
+
+        let tests = RepoGlob::new("acme/*-test?").unwrap();
+        assert!(tests.matches("acme", "api-tests"));
+        assert!(!tests.matches("acme", "api-test"));
+        assert!(!tests.matches("other", "api-tests"));
+
+        // Regex syntax is literal
+        assert!(RepoGlob::new("a.b").unwrap().matches("acme", "a.b"));
+        assert!(!RepoGlob::new("a.b").unwrap().matches("acme", "axb"));
+
+        for invalid in ["", "acme/", "/api", "a/b/c"] {
+            assert!(RepoGlob::new(invalid).is_err(), "{}", invalid);
+        }
+    }
+
+    #[test]
+    fn test_set_exclude_repos() {
+        let mut config = Config::default();
+        config
+            .set_exclude_repos(vec!["*-sandbox".to_string(), "acme/*-test".to_string()])
+            .unwrap();
+        assert_eq!(config.exclude_repos, ["*-sandbox", "acme/*-test"]);
+        assert_eq!(config.exclude_repo_globs().unwrap().len(), 2);
+
+        assert!(config
+            .set_exclude_repos(vec!["ok".to_string(), "a/b/c".to_string()])
+            .is_err());
+        assert_eq!(config.exclude_repos.len(), 2);
+
+        config.set_exclude_repos(vec!["none".to_string()]).unwrap();
+        assert!(config.exclude_repos.is_empty());
+    }
+
+    #[test]
+    fn test_effective_repo_pattern() {
+        let config: Config = toml::from_str(
+            r#"
+            orgs = ["org1"]
+            username = "me"
+            repo_patterns = ["^backend-", "^api-(v1|v2)$"]
+            "#,
+        )
+        .unwrap();
+        let pattern = config.effective_repo_pattern().unwrap();
+        assert_eq!(pattern, "(?:^backend-)|(?:^api-(v1|v2)$)");
+        let regex = compile_repo_pattern(&pattern).unwrap();
+        assert!(regex.is_match("backend-jobs"));
+        assert!(regex.is_match("api-v2"));
+        assert!(!regex.is_match("my-backend-jobs"));
+        assert!(!regex.is_match("api-v3"));
+
+        assert_eq!(Config::default().effective_repo_pattern(), None);
+        let single = Config {
+            repo_pattern: Some("web-.*".to_string()),
+            ..Default::default()
+        };
+        assert_eq!(single.effective_repo_pattern().as_deref(), Some("web-.*"));
+    }
+
+    #[test]
+    fn test_last_scan_save_load_and_retry() {
+        let temp_dir = TempDir::new().unwrap();
+        let path = temp_dir.path().join("last-scan.json");
+        assert_eq!(LastScan::load_from_path(&path).unwrap(), None);
+
+        let pr = |repo: &str, number: u32| PullRequest {
+            number,
+            html_url: format!("https://github.com/{}/pull/{}", repo, number),
+            repo: repo.to_string(),
+            ..Default::default()
+        };
+        let scan = LastScan {
+            mode: SearchMode::Review,
+            prs: vec![pr("org1/api", 1)],
+            failed: vec![FailedRepo {
+                host: None,
+                repo: "org1/web".to_string(),
+                username: "testuser".to_string(),
+            }],
+        };
+        scan.save_to_path(&path).unwrap();
+        let loaded = LastScan::load_from_path(&path).unwrap().unwrap();
+        assert_eq!(loaded, scan);
+
+        let merged = loaded.with_retried(vec![pr("org1/web", 2), pr("org1/api", 1)]);
+        assert_eq!(
+            merged.iter().map(|pr| pr.number).collect::<Vec<_>>(),
+            vec![1, 2]
+        );
+
+        LastScan::clear_at_path(&path).unwrap();
+        assert!(!path.exists());
+    }
+
+    #[test]
+    fn test_checkpoint_save_load_and_clear() {
+        let temp_dir = TempDir::new().unwrap();
+        let path = temp_dir.path().join("checkpoint.json");
+
+        // No checkpoint yet
+        assert_eq!(Checkpoint::load_from_path(&path).unwrap(), None);
+
+        let mut checkpoint = Checkpoint::new("review|testuser|org1|".to_string());
+        checkpoint.completed.push("org1/api".to_string());
+        checkpoint.prs.push(PullRequest {
+            number: 7,
+            title: "Fix it".to_string(),
+            html_url: "https://github.com/org1/api/pull/7".to_string(),
+            user: User {
+                login: "alice".to_string(),
+            },
+            repo: "org1/api".to_string(),
+            ..Default::default()
+        });
+        checkpoint.save_to_path(&path).unwrap();
+
+        let loaded = Checkpoint::load_from_path(&path).unwrap();
+        assert_eq!(loaded, Some(checkpoint));
+
+        Checkpoint::clear_at_path(&path).unwrap();
+        assert!(!path.exists());
+        // Clearing a missing checkpoint is fine
+        Checkpoint::clear_at_path(&path).unwrap();
+    }
+
+    #[test]
+    fn test_pr_json_fields() {
+        let merge_state = PrFields {
+            merge_state: true,
+            ..Default::default()
+        };
+        assert_eq!(
+            pr_json_fields(false, PrFields::default()),
+            "number,title,url,author,reviewRequests"
+        );
+        assert_eq!(
+            pr_json_fields(true, PrFields::default()),
+            "number,title,url,author"
+        );
+        assert_eq!(
+            pr_json_fields(true, merge_state),
+            "number,title,url,author,mergeable,mergeStateStatus"
+        );
+        assert_eq!(
+            pr_json_fields(
+                true,
+                PrFields {
+                    review_decision: true,
+                    ..merge_state
+                }
+            ),
+            "number,title,url,author,mergeable,mergeStateStatus,reviewDecision"
+        );
+        assert_eq!(
+            pr_json_fields(
+                false,
+                PrFields {
+                    inbox: true,
+                    ..Default::default()
+                }
+            ),
+            "number,title,url,author,reviewRequests,assignees,body"
+        );
+    }
+
+    #[test]
+    fn test_mentions() {
+        assert!(mentions("cc @alice", "alice"));
+        assert!(mentions("@Alice, thoughts?", "alice"));
+        assert!(mentions("(@alice)", "ALICE"));
+        assert!(!mentions("cc @alice-bot", "alice"));
+        assert!(!mentions("cc @alicebob", "alice"));
+        assert!(!mentions("mail bob@alice.dev", "alice"));
+        assert!(!mentions("alice", "alice"));
+    }
+
+    #[test]
+    fn test_sort_prs() {
+        let pr = |number: u32, repo: &str, title: &str, created_at: Option<&str>| PullRequest {
+            number,
+            repo: repo.to_string(),
+            title: title.to_string(),
+            created_at: created_at.map(str::to_string),
+            ..Default::default()
+        };
+        let prs = vec![
+            pr(7, "org/web", "b", Some("2024-03-01T00:00:00Z")),
+            pr(2, "org/Api", "C", None),
+            pr(5, "org/api", "a", Some("2024-01-01T00:00:00Z")),
+        ];
+        let sorted = |key: SortKey| {
+            let mut prs = prs.clone();
+            sort_prs(&mut prs, key);
+            prs.iter().map(|pr| pr.number).collect::<Vec<_>>()
+        };
+        assert_eq!(sorted(SortKey::Age), [5, 7, 2]);
+        assert_eq!(sorted(SortKey::Number), [2, 5, 7]);
+        assert_eq!(sorted(SortKey::Title), [5, 7, 2]);
+        assert_eq!(sorted(SortKey::Repo), [2, 5, 7]);
+    }
+
+    #[test]
+    fn test_sort_inbox() {
+        let pr = |number: u32, reason: InboxReason, created_at: Option<&str>| PullRequest {
+            number,
+            reasons: vec![reason],
+            created_at: created_at.map(str::to_string),
+            ..Default::default()
+        };
+        let mut prs = vec![
+            pr(1, InboxReason::Mention, Some("2024-01-01T00:00:00Z")),
+            pr(2, InboxReason::Review, None),
+            pr(3, InboxReason::Assignee, Some("2024-01-01T00:00:00Z")),
+            pr(4, InboxReason::Review, Some("2024-02-01T00:00:00Z")),
+            pr(5, InboxReason::Review, Some("2024-01-01T00:00:00Z")),
+        ];
+        sort_inbox(&mut prs);
+        let order: Vec<u32> = prs.iter().map(|pr| pr.number).collect();
+        assert_eq!(order, [5, 4, 2, 3, 1]);
+    }
+
+    #[test]
+    fn test_review_request_team_name() {
+        let requests: Vec<GhReviewRequest> = serde_json::from_str(
+            r#"[
+                {"__typename":"User","login":"alice"},
+                {"__typename":"Team","name":"Core","slug":"core"},
+                {"__typename":"Team","name":"Infra","slug":"other-org/infra"}
+            ]"#,
+        )
+        .unwrap();
+
+        assert_eq!(requests[0].team_name("org1"), None);
+        assert_eq!(requests[1].team_name("org1"), Some("org1/core".to_string()));
+        assert_eq!(
+            requests[2].team_name("org1"),
+            Some("other-org/infra".to_string())
+        );
+    }
+
+    #[test]
+    fn test_gh_pull_request_has_conflicts() {
+        let parse = |extra: &str| -> GhPullRequest {
+            serde_json::from_str(&format!(
+                r#"{{"number":1,"title":"t","url":"u","author":{{"login":"me"}}{}}}"#,
+                extra
+            ))
+            .unwrap()
+        };
+
+        assert!(!parse("").has_conflicts());
+        assert!(!parse(r#","mergeable":"MERGEABLE","mergeStateStatus":"CLEAN""#).has_conflicts());
+        assert!(parse(r#","mergeable":"CONFLICTING""#).has_conflicts());
+        assert!(parse(r#","mergeable":"UNKNOWN","mergeStateStatus":"DIRTY""#).has_conflicts());
+    }
+
+    #[test]
+    fn test_gh_search_pull_request() {
+        let pr: GhSearchPullRequest = serde_json::from_str(
+            r#"{"number": 7, "title": "Fix", "url": "u", "author": {"login": "alice"},
+                "repository": {"name": "api", "nameWithOwner": "org1/api"}}"#,
+        )
+        .unwrap();
+        assert_eq!(pr.repository.name_with_owner, "org1/api");
+        assert_eq!(pr.author.login, "alice");
+    }
+
+    #[test]
+    fn test_gh_pull_request_without_review_requests() {
+        let json = r#"[{"number":1,"title":"t","url":"u","author":{"login":"me"}}]"#;
+        let prs: Vec<GhPullRequest> = serde_json::from_str(json).unwrap();
+        assert!(prs[0].review_requests.is_empty());
+    }
+
+    #[test]
+    fn test_visibility_matches() {
+        let repo: GhRepo =
+            serde_json::from_str(r#"{"name": "api", "visibility": "PRIVATE"}"#).unwrap();
+        assert!(Visibility::Private.matches(repo.visibility.as_deref()));
+        assert!(!Visibility::Public.matches(repo.visibility.as_deref()));
+        assert!(Visibility::Internal.matches(Some("internal")));
+        assert!(!Visibility::Public.matches(None));
+    }
+
+    #[test]
+    fn test_parse_date() {
+        assert_eq!(parse_date("2024-01-15").unwrap(), 1_705_276_800);
+        assert_eq!(parse_date("2024-01-15T10:30:00Z").unwrap(), 1_705_314_600);
+        assert!(parse_date("15/01/2024").is_err());
+        assert!(parse_date("2024-13-01").is_err());
+    }
+
+    #[test]
+    fn test_parse_timestamp() {
+        assert_eq!(parse_timestamp("1970-01-01T00:00:00Z"), Some(0));
+        assert_eq!(parse_timestamp("2024-01-15T10:30:00Z"), Some(1_705_314_600));
+        assert_eq!(parse_timestamp("2024-02-29T23:59:59Z"), Some(1_709_251_199));
+        assert_eq!(parse_timestamp("2024-01-15"), None);
+        assert_eq!(parse_timestamp("2024-13-01T00:00:00Z"), None);
+        assert_eq!(parse_timestamp("garbage"), None);
+    }
+
+    #[test]
+    fn test_format_date() {
+        assert_eq!(format_date(0), "1970-01-01");
+        assert_eq!(format_date(1_705_314_600), "2024-01-15");
+        assert_eq!(format_date(1_709_251_199), "2024-02-29");
+        assert_eq!(format_date(1_709_251_200), "2024-03-01");
+        assert_eq!(format_timestamp(1_705_314_600), "2024-01-15T10:30:00Z");
+        assert_eq!(
+            parse_timestamp(&format_timestamp(1_709_251_199)),
+            Some(1_709_251_199)
+        );
+    }
+
+    #[test]
+    fn test_pull_request_age_days() {
+        let mut pr = PullRequest {
+            created_at: Some("2024-01-15T10:30:00Z".to_string()),
+            ..Default::default()
+        };
+        assert_eq!(pr.age_days(1_705_314_600 + 8 * 86_400 + 5), Some(8));
+        assert_eq!(pr.age_days(0), Some(0));
+        pr.created_at = None;
+        assert_eq!(pr.age_days(1_705_314_600), None);
+    }
+
+    #[test]
+    fn test_closed_without_review() {
+        let mut pr = PullRequest::default();
+        assert!(!pr.closed_without_review());
+        pr.state = Some("OPEN".to_string());
+        assert!(!pr.closed_without_review());
+        pr.state = Some("MERGED".to_string());
+        assert!(pr.closed_without_review());
+        pr.state = Some("CLOSED".to_string());
+        assert!(pr.closed_without_review());
+    }
+
+    #[test]
+    fn test_read_token_file() {
+        let temp_dir = TempDir::new().unwrap();
+        let path = temp_dir.path().join("token");
+
+        fs::write(&path, "ghp_secret\n").unwrap();
+        assert_eq!(read_token_file(&path).unwrap(), "ghp_secret");
+
+        fs::write(&path, "  \n").unwrap();
+        assert!(read_token_file(&path).is_err());
+        assert!(read_token_file(&temp_dir.path().join("missing")).is_err());
+    }
+
+    #[test]
+    fn test_write_atomically() {
+        let temp_dir = TempDir::new().unwrap();
+        let path = temp_dir.path().join("count");
+
+        write_atomically(&path, "3\n").unwrap();
+        write_atomically(&path, "5\n").unwrap();
+        assert_eq!(fs::read_to_string(&path).unwrap(), "5\n");
+        // Nothing but the file itself is left behind
+        assert_eq!(fs::read_dir(temp_dir.path()).unwrap().count(), 1);
+
+        assert!(write_atomically(&temp_dir.path().join("missing").join("count"), "1\n").is_err());
+    }
+
+    #[test]
+    fn test_is_network_error() {
+        assert!(is_network_error(
+            "error connecting to api.github.com\ncheck your internet connection"
+        ));
+        assert!(is_network_error(
+            "dial tcp: lookup api.github.com: no such host"
+        ));
+        assert!(is_network_error("net/http: TLS handshake timeout"));
+        assert!(!is_network_error(
+            "You are not logged into any GitHub hosts. To log in, run: gh auth login"
+        ));
+        assert!(!is_network_error(""));
+    }
+
+    #[test]
+    fn test_sample() {
+        let items: Vec<u32> = (0..100).collect();
+
+        let picked = sample(items.clone(), 10, 42);
+        assert_eq!(picked.len(), 10);
+        // Original order is kept and there are no duplicates
+        assert!(picked.windows(2).all(|w| w[0] < w[1]));
+        // Same seed, same sample
+        assert_eq!(sample(items.clone(), 10, 42), picked);
+        assert_ne!(sample(items.clone(), 10, 7), picked);
+
+        // Asking for more than there is returns everything
+        assert_eq!(sample(vec![1, 2, 3], 5, 1), vec![1, 2, 3]);
+        assert!(sample(items, 0, 1).is_empty());
+    }
+
+    #[test]
+    fn test_parallel_map_keeps_input_order() {
+        // Earlier items take longer, so they tend to finish last
+        let items: Vec<u64> = (0..40).collect();
+        let expected: Vec<u64> = items.iter().map(|i| i * 2).collect();
+        for workers in [1, 4, 16, 100] {
+            let results = parallel_map(items.clone(), workers, |i| {
+                thread::sleep(std::time::Duration::from_millis((40 - i) % 7));
+                i * 2
+            });
+            assert_eq!(results, expected, "with {} workers", workers);
+        }
+        assert!(parallel_map(Vec::<u64>::new(), 0, |i| i).is_empty());
+    }
+
+    #[test]
+    fn test_parallel_each_stops_on_error() {
+        let mut handled = Vec::new();
+        let result = parallel_each(
+            (0..100).collect(),
+            1,
+            |i: u32| i,
+            |_, i| {
+                handled.push(i);
+                if i == 3 {
+                    Err(i)
+                } else {
+                    Ok(())
+                }
+            },
+        );
+        assert_eq!(result, Err(3));
+        assert_eq!(handled, [0, 1, 2, 3]);
+    }
+
+    #[test]
+    fn test_match_history() {
+        let temp_dir = TempDir::new().unwrap();
+        let path = temp_dir.path().join("state").join("matched-logins.json");
+        assert_eq!(MatchHistory::load_from_path(&path), MatchHistory::default());
+
+        let mut history = MatchHistory::default();
+        history.matched.insert("testuser".to_string());
+        history.save_to_path(&path).unwrap();
+        assert_eq!(MatchHistory::load_from_path(&path), history);
+
+        fs::write(&path, "not json").unwrap();
+        assert_eq!(MatchHistory::load_from_path(&path), MatchHistory::default());
+    }
+
+    #[test]
+    fn test_history_append_and_load() {
+        let temp_dir = TempDir::new().unwrap();
+        let path = temp_dir.path().join("history.jsonl");
+        assert!(History::load_from_path(&path).unwrap().is_empty());
+
+        let entry = |at: u64| HistoryEntry {
+            at,
+            prs: vec![HistoryPr {
+                url: "https://github.com/org/a/pull/1".to_string(),
+                created_at: None,
+            }],
+        };
+        History::append_to_path(&path, &entry(200)).unwrap();
+        History::append_to_path(&path, &entry(100)).unwrap();
+        assert_eq!(
+            History::load_from_path(&path).unwrap(),
+            vec![entry(100), entry(200)]
+        );
+
+        fs::write(&path, "{\"at\": 1, \"prs\": []}\nnot json\n").unwrap();
+        let err = History::load_from_path(&path).unwrap_err().to_string();
+        assert!(err.starts_with("Bad entry on line 2"), "{}", err);
+    }
+
+    #[test]
+    fn test_history_stats() {
+        const DAY: u64 = 86_400;
+        // Monday 2024-01-15
+        let monday = 1_705_276_800;
+        let pr = |n: u32| HistoryPr {
+            url: format!("https://github.com/org/a/pull/{}", n),
+            created_at: Some("2024-01-13T00:00:00Z".to_string()),
+        };
+        let entries = vec![
+            HistoryEntry {
+                at: monday,
+                prs: vec![pr(1), pr(2)],
+            },
+            HistoryEntry {
+                at: monday + DAY,
+                prs: vec![pr(2), pr(3)],
+            },
+            HistoryEntry {
+                at: monday + 7 * DAY,
+                prs: vec![pr(3)],
+            },
+        ];
+
+        let stats = history_stats(&entries).unwrap();
+        assert_eq!(stats.runs, 3);
+        assert_eq!(stats.backlog, 1);
+        assert_eq!(stats.oldest_backlog_days, Some(9));
+        // #1 took a day, #2 a week
+        assert_eq!(stats.reviewed, 2);
+        assert_eq!(stats.average_review_secs, Some(4 * DAY));
+        assert_eq!(
+            stats.reviewed_per_week,
+            BTreeMap::from([("2024-01-15".to_string(), 1), ("2024-01-22".to_string(), 1)])
+        );
+
+        assert_eq!(history_stats(&[]), None);
+    }
+
+    #[test]
+    fn test_permission_from_gh() {
+        assert_eq!(Permission::from_gh("WRITE"), Some(Permission::Write));
+        assert_eq!(Permission::from_gh("maintain"), Some(Permission::Maintain));
+        assert_eq!(Permission::from_gh("NONE"), None);
+        assert!(Permission::Admin > Permission::Write && Permission::Triage > Permission::Read);
+    }
+
+    #[test]
+    fn test_repo_cache() {
+        let temp_dir = TempDir::new().unwrap();
+        let path = temp_dir.path().join("repo-cache.json");
+        let repos = vec![GhRepo {
+            name: "api".to_string(),
+            visibility: Some("PRIVATE".to_string()),
+            pushed_at: Some("2024-01-15T10:30:00Z".to_string()),
+            viewer_permission: Some("WRITE".to_string()),
+            org: String::new(),
+        }];
+
+        assert_eq!(RepoCache::load_from_path(&path), RepoCache::default());
+        let mut cache = RepoCache::default();
+        cache.insert("org1", repos.clone(), 1000);
+        cache.save_to_path(&path).unwrap();
+
+        let cache = RepoCache::load_from_path(&path);
+        assert_eq!(cache.fresh("org1", 1000 + 60), Some(repos.as_slice()));
+        assert_eq!(cache.fresh("org1", 1000 + REPO_CACHE_TTL_SECS), None);
+        assert_eq!(cache.fresh("org2", 1000), None);
+
+        fs::write(&path, "not json").unwrap();
+        assert_eq!(RepoCache::load_from_path(&path), RepoCache::default());
+    }
+
+    #[test]
+    fn test_cached_identity() {
+        let temp_dir = TempDir::new().unwrap();
+        let path = temp_dir.path().join("identity.json");
+        assert_eq!(CachedIdentity::load_from_path(&path).unwrap(), None);
+
+        let identity = CachedIdentity {
+            login: "octocat".to_string(),
+            fetched_at: 1_000,
+            auth_stamp: Some(42),
+        };
+        identity.save_to_path(&path).unwrap();
+        assert_eq!(
+            CachedIdentity::load_from_path(&path).unwrap(),
+            Some(identity.clone())
+        );
+
+        assert!(identity.is_fresh(1_000 + 60, Some(42)));
+        // Expired
+        assert!(!identity.is_fresh(1_000 + IDENTITY_TTL_SECS, Some(42)));
+        // gh auth changed since
+        assert!(!identity.is_fresh(1_000 + 60, Some(43)));
+        assert!(!identity.is_fresh(1_000 + 60, None));
+    }
+
+    #[test]
+    fn test_expand_org_prefix() {
+        let memberships: Vec<String> = ["acme-eng", "Acme-Infra", "acmecorp", "other"]
+            .iter()
+            .map(|s| s.to_string())
+            .collect();
+        assert_eq!(expand_org_prefix("oth", &memberships).unwrap(), ["other"]);
+        assert_eq!(
+            expand_org_prefix("acme-", &memberships).unwrap(),
+            ["acme-eng", "Acme-Infra"]
+        );
+        let err = expand_org_prefix("acme", &memberships)
+            .unwrap_err()
+            .to_string();
+        assert!(err.contains("acme-eng, Acme-Infra, acmecorp"), "{}", err);
+        assert!(expand_org_prefix("nope", &memberships).is_err());
+
+        let family = vec!["acme-eng".to_string(), "acme-infra".to_string()];
+        assert_eq!(
+            expand_org_prefix("ACME", &family).unwrap(),
+            ["acme-eng", "acme-infra"]
+        );
+    }
+
+    #[test]
+    fn test_over_sla() {
+        let opened = parse_timestamp("2024-01-15T10:30:00Z").unwrap();
+        let mut pr = PullRequest {
+            created_at: Some("2024-01-15T10:30:00Z".to_string()),
+            ..Default::default()
+        };
+        assert_eq!(pr.over_sla(opened + 7_200), None);
+        pr.sla_hours = Some(24);
+        assert_eq!(pr.over_sla(opened + 24 * 3_600), Some(false));
+        assert_eq!(pr.over_sla(opened + 24 * 3_600 + 1), Some(true));
+        pr.created_at = None;
+        assert_eq!(pr.over_sla(opened), None);
+
+        let config: Config =
+            toml::from_str("orgs = [\"acme\"]\nusername = \"me\"\n[review_sla_hours]\nacme = 24\n")
+                .unwrap();
+        assert_eq!(config.review_sla_hours.get("acme"), Some(&24));
+    }
+
+    #[test]
+    fn test_host_mut() {
+        let mut config = Config::default();
+        config
+            .host_mut("github.example.com")
+            .orgs
+            .push("corp".to_string());
+        config.host_mut("github.example.com").username = "me-at-corp".to_string();
+        assert_eq!(
+            config.hosts,
+            vec![HostConfig {
+                host: "github.example.com".to_string(),
+                orgs: vec!["corp".to_string()],
+                username: "me-at-corp".to_string(),
+            }]
+        );
+        assert_eq!(config.host_mut("other.example.com").username, "@me");
+        assert_eq!(config.hosts.len(), 2);
+    }
+
+    #[test]
+    fn test_parse_project() {
+        assert_eq!(
+            parse_project("acme/12", None).unwrap(),
+            ("acme".to_string(), 12)
+        );
+        assert_eq!(
+            parse_project("@me/3", Some("acme")).unwrap(),
+            ("@me".to_string(), 3)
+        );
+        assert_eq!(
+            parse_project("7", Some("acme")).unwrap(),
+            ("acme".to_string(), 7)
+        );
+        let err = parse_project("7", None).unwrap_err().to_string();
+        assert!(err.contains("--project my-org/7"), "{}", err);
+        assert!(parse_project("acme/x", None).is_err());
+        assert!(parse_project("/7", None).is_err());
+    }
+
+    #[test]
+    fn test_dedup_orgs() {
+        let orgs = |list: &str| list.split(',').map(str::to_string).collect::<Vec<_>>();
+        assert_eq!(
+            dedup_orgs(orgs("org1,org1,org2")),
+            (orgs("org1,org2"), orgs("org1"))
+        );
+        assert_eq!(dedup_orgs(orgs("b,a,B,a")), (orgs("b,a"), orgs("B,a")));
+
+        let mut config = Config::default();
+        config.set_orgs(orgs("org1,org1,org2"));
+        assert_eq!(config.orgs, orgs("org1,org2"));
+    }
+
+    #[test]
+    fn test_parse_selection() {
+        assert_eq!(parse_selection("1,3-5", 6).unwrap(), vec![0, 2, 3, 4]);
+        assert_eq!(parse_selection(" 2, 2 ,1-2 ", 3).unwrap(), vec![0, 1]);
+        assert_eq!(parse_selection("", 3).unwrap(), Vec::<usize>::new());
+        assert!(parse_selection("4", 3).is_err());
+        assert!(parse_selection("0", 3).is_err());
+        assert!(parse_selection("3-1", 3).is_err());
+        assert!(parse_selection("two", 3).is_err());
+    }
+
     #[test]
     fn test_parse_org_modification() {
         // Test add
@@ -1,11 +1,23 @@
 use anyhow::Result;
-use clap::{Parser, Subcommand};
-use regex::Regex;
+use clap::{Parser, Subcommand, ValueEnum};
+use review_radar::format::{self, ColorChoice, OutputFormat, RunMetadata, TargetMetadata};
+use review_radar::search::{
+    gh_command, GitHubClient, ProgressStyle, Reporter, ScanOptions, SearchResult,
+    DEFAULT_CONCURRENCY, MAX_PAGE_SIZE,
+};
 use review_radar::{
-    parse_org_modification, Config, GhPullRequest, GhRepo, OrgModification, PullRequest, User,
+    compile_repo_pattern, dedup_orgs, expand_org_prefix, format_date, format_timestamp,
+    history_stats, is_network_error, parse_date, parse_org_modification, parse_project,
+    parse_selection, read_token_file, sort_inbox, sort_prs, write_atomically, Config, FailedRepo,
+    History, HistoryEntry, HistoryPr, LastScan, MatchHistory, OrgModification, Permission,
+    PrFields, PullRequest, SearchMode, SortKey, Visibility,
 };
-use std::io::Write;
-use std::process::Command;
+use std::collections::BTreeMap;
+use std::io::{IsTerminal, Write};
+use std::path::PathBuf;
+use std::process::{Command, Stdio};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
 
 #[derive(Parser, Debug)]
 #[command(name = "review-radar")]
@@ -14,12 +26,39 @@ struct Args {
     #[command(subcommand)]
     command: Option<Commands>,
 
+    #[arg(
+        value_name = "OWNER/REPO",
+        conflicts_with_all = ["orgs", "skip_orgs", "repo_pattern", "query", "sample", "visibility"],
+        help = "Scan only this repository instead of your organizations"
+    )]
+    repo: Option<String>,
+
     #[arg(long, help = "Override configured organization(s), comma-separated")]
     orgs: Option<String>,
 
-    #[arg(short, long, help = "Override configured username")]
+    #[arg(
+        long = "skip-org",
+        value_name = "ORG",
+        help = "Leave out this organization for this run (repeatable)"
+    )]
+    skip_orgs: Vec<String>,
+
+    #[arg(
+        short,
+        long,
+        help = "Override configured username ('@me' for the authenticated user)"
+    )]
     username: Option<String>,
 
+    #[arg(
+        long = "for",
+        value_name = "LOGIN",
+        value_delimiter = ',',
+        conflicts_with_all = ["username", "own_prs", "mode", "query", "team_only"],
+        help = "Show the PRs awaiting review from these logins instead of you, e.g. to balance a team's load (comma-separated or repeated)"
+    )]
+    for_users: Vec<String>,
+
     #[arg(
         short = 'o',
         long = "own",
@@ -27,12 +66,321 @@ struct Args {
     )]
     own_prs: bool,
 
+    #[arg(
+        long,
+        value_enum,
+        conflicts_with = "own_prs",
+        help = "Which PRs to look for [default: review, or 'mode' under [defaults] in the config]"
+    )]
+    mode: Option<SearchMode>,
+
     #[arg(
         short = 'r',
         long = "repo-pattern",
         help = "Regex pattern to filter repository names (e.g., 'void-.*')"
     )]
     repo_pattern: Option<String>,
+
+    #[arg(
+        long,
+        value_name = "PATTERN",
+        help = "Keep only PRs whose title matches this regex (e.g. '^fix:')"
+    )]
+    title_pattern: Option<String>,
+
+    #[arg(
+        long,
+        value_name = "PATTERN",
+        help = "Leave out PRs whose title matches this regex (e.g. 'WIP')"
+    )]
+    exclude_title_pattern: Option<String>,
+
+    #[arg(
+        long,
+        value_name = "SEARCH",
+        conflicts_with_all = [
+            "orgs", "own_prs", "mode", "repo_pattern", "resume", "sample",
+            "conflicts_only", "team_only", "visibility", "include_closed_requests",
+        ],
+        help = "Find PRs with a raw GitHub search query (e.g. 'review-requested:@me is:open') instead of scanning orgs"
+    )]
+    query: Option<String>,
+
+    #[arg(
+        long,
+        value_enum,
+        help = "Output format [default: text, or 'format' under [defaults] in the config]"
+    )]
+    format: Option<OutputFormat>,
+
+    #[arg(
+        long,
+        conflicts_with = "format",
+        help = "Print just the PR list as a JSON array, with progress on stderr (same as '--format json --no-metadata')"
+    )]
+    json: bool,
+
+    #[arg(
+        long,
+        help = "Resume an interrupted scan, skipping repositories it already checked"
+    )]
+    resume: bool,
+
+    #[arg(
+        long,
+        value_name = "FILE",
+        required_if_eq("format", "delta"),
+        help = "Snapshot saved with '--format json' to compare against (for '--format delta')"
+    )]
+    baseline: Option<PathBuf>,
+
+    #[arg(
+        long,
+        value_name = "N",
+        help = "Scan only N randomly picked repositories for a quick spot check"
+    )]
+    sample: Option<usize>,
+
+    #[arg(
+        long,
+        requires = "sample",
+        help = "Seed for '--sample' so the same repositories are picked again"
+    )]
+    seed: Option<u64>,
+
+    #[arg(
+        long,
+        help = "Show only PRs with merge conflicts (conflicts are always flagged with --own)"
+    )]
+    conflicts_only: bool,
+
+    #[arg(
+        long,
+        conflicts_with = "own_prs",
+        help = "Show only review requests routed through one of your teams, not direct ones"
+    )]
+    team_only: bool,
+
+    #[arg(
+        long,
+        conflicts_with = "own_prs",
+        help = "Also list closed and merged PRs that requested your review but never got it"
+    )]
+    include_closed_requests: bool,
+
+    #[arg(
+        long,
+        value_name = "N",
+        help = "Most PRs to list per repository, warning when a repository has more [default: 1000]"
+    )]
+    pr_limit: Option<usize>,
+
+    #[arg(
+        long = "gh-arg",
+        value_name = "ARG",
+        allow_hyphen_values = true,
+        help = "Pass ARG through to every 'gh pr list' call (repeatable); repositories where gh rejects it are skipped"
+    )]
+    gh_args: Vec<String>,
+
+    #[arg(
+        long,
+        conflicts_with = "query",
+        help = "Say under each PR which checks it passed to be shown, for tuning filters"
+    )]
+    explain: bool,
+
+    #[arg(
+        long,
+        help = "Find review requests by listing every repository's PRs instead of with one GitHub search"
+    )]
+    legacy_scan: bool,
+
+    /// Internal: parse every PR locally instead of filtering inside gh with --jq.
+    #[arg(long, hide = true)]
+    no_jq: bool,
+
+    #[arg(
+        long,
+        value_name = "[OWNER/]NUMBER",
+        help = "Keep only PRs on this GitHub project; OWNER defaults to your only configured org"
+    )]
+    project: Option<String>,
+
+    #[arg(long, value_enum, help = "Scan only repositories with this visibility")]
+    visibility: Option<Visibility>,
+
+    #[arg(
+        long,
+        value_enum,
+        value_name = "LEVEL",
+        help = "Scan only repositories where you have at least this access"
+    )]
+    min_permission: Option<Permission>,
+
+    #[arg(
+        long,
+        value_name = "N",
+        value_parser = clap::value_parser!(u16).range(1..=MAX_PAGE_SIZE as i64),
+        help = "Results per page when listing your teams and orgs through the API, up to 100 [default: GitHub's 30]"
+    )]
+    page_size: Option<u16>,
+
+    #[arg(
+        long,
+        value_name = "N",
+        help = "List the PRs of this many repositories at once, 0 counting as 1 [default: 8, or 'concurrency' under [defaults] in the config]"
+    )]
+    concurrency: Option<usize>,
+
+    #[arg(
+        long,
+        value_enum,
+        default_value_t = ProgressStyle::Auto,
+        help = "Report scan progress per repository or per org (auto: per org for more than 5 orgs)"
+    )]
+    progress: ProgressStyle,
+
+    #[arg(
+        long,
+        help = "List each org's repositories again instead of using the cached lists (kept for an hour)"
+    )]
+    refresh_repos: bool,
+
+    #[arg(
+        long,
+        help = "Look up the login '@me' stands for again instead of using the cached one"
+    )]
+    refresh_identity: bool,
+
+    #[arg(
+        long,
+        value_name = "FILE",
+        help = "Read the GitHub token from FILE and pass it to gh as GH_TOKEN [env: REVIEW_RADAR_TOKEN_FILE]"
+    )]
+    token_file: Option<PathBuf>,
+
+    #[arg(
+        long,
+        help = "Leave the run's settings out of '--format json' output, printing just the PR list"
+    )]
+    no_metadata: bool,
+
+    #[arg(
+        long,
+        help = "Show one desktop notification with the number of PRs found when the scan ends"
+    )]
+    notify_summary: bool,
+
+    #[arg(
+        long,
+        help = "Fail on the first org or repository that can't be listed instead of skipping it"
+    )]
+    strict: bool,
+
+    #[arg(
+        long,
+        conflicts_with_all = ["query", "for_users", "sample"],
+        help = "Append the PRs awaiting your review to the history 'rr stats' reports on"
+    )]
+    record_history: bool,
+
+    #[arg(
+        long,
+        value_name = "DATE",
+        help = "Skip repositories nothing was pushed to since DATE (YYYY-MM-DD)"
+    )]
+    repos_active_since: Option<String>,
+
+    #[arg(
+        long,
+        value_name = "URL",
+        help = "POST the PRs found to this Discord webhook (as with --format discord), when there are any"
+    )]
+    discord_webhook: Option<String>,
+
+    #[arg(
+        long,
+        value_name = "FILE",
+        help = "Overwrite FILE with just the number of PRs found after each run, atomically (for status bars)"
+    )]
+    status_file: Option<PathBuf>,
+
+    #[arg(
+        long = "output",
+        value_name = "FILE:FORMAT",
+        value_parser = format::parse_output_spec,
+        help = "Also write the results to FILE in FORMAT, e.g. report.json:json; repeat for several"
+    )]
+    outputs: Vec<(PathBuf, OutputFormat)>,
+
+    #[arg(
+        long,
+        value_enum,
+        default_value_t,
+        help = "Color PRs by how long they've waited: auto colors a terminal unless NO_COLOR is set"
+    )]
+    color: ColorChoice,
+
+    #[arg(
+        long,
+        help = "Make PR titles clickable terminal links instead of printing each URL (only on a terminal)"
+    )]
+    hyperlinks: bool,
+
+    #[arg(
+        long,
+        value_name = "N",
+        default_value_t = 3,
+        help = "List the N longest-waiting PRs above the text output's full list (0 to turn off)"
+    )]
+    highlight_oldest: usize,
+
+    #[arg(
+        long,
+        value_name = "N",
+        help = "Cut PR titles in text and kanban output to N characters with an ellipsis [default: fit the terminal, 0 never cuts]"
+    )]
+    title_width: Option<usize>,
+
+    #[arg(
+        long,
+        value_name = "PREFIX",
+        help = "Replace the scheme and host of PR links with PREFIX, e.g. '/' for host-relative links"
+    )]
+    url_base: Option<String>,
+
+    #[arg(
+        long,
+        value_enum,
+        help = "Order the PRs found [default: as found, or 'sort' under [defaults] in the config]"
+    )]
+    sort: Option<SortKey>,
+
+    #[arg(long, help = "Never pipe long output through $PAGER")]
+    no_pager: bool,
+
+    #[arg(
+        short,
+        long,
+        help = "Don't ask for confirmation before scanning a very large number of repositories"
+    )]
+    yes: bool,
+
+    #[arg(
+        short,
+        long,
+        help = "Print extra details: login matched per org, timings, skipped repositories"
+    )]
+    verbose: bool,
+
+    #[arg(
+        short,
+        long,
+        conflicts_with = "verbose",
+        help = "Print only the results: no progress output and no surrounding blank lines"
+    )]
+    quiet: bool,
 }
 
 #[derive(Subcommand, Debug)]
@@ -40,233 +388,225 @@ enum Commands {
     #[command(about = "Initialize configuration")]
     Init {
         #[arg(help = "GitHub organization(s), comma-separated")]
-        orgs: String,
-        #[arg(help = "Your GitHub username")]
-        username: String,
+        orgs: Option<String>,
+        #[arg(help = "Your GitHub username", requires = "orgs")]
+        username: Option<String>,
         #[arg(
             short = 'r',
             long = "repo-pattern",
             help = "Regex pattern to filter repository names"
         )]
         repo_pattern: Option<String>,
+        #[arg(
+            long,
+            conflicts_with_all = ["orgs", "username", "repo_pattern"],
+            help = "Ask for each setting step by step (the default for a bare 'rr init' in a terminal)"
+        )]
+        interactive: bool,
     },
     #[command(about = "Update configuration")]
     Set {
         #[arg(
             long,
+            allow_hyphen_values = true,
             help = "GitHub organization(s), comma-separated (use '+org' to add, '-org' to remove)"
         )]
         orgs: Option<String>,
         #[arg(long, help = "Your GitHub username")]
         username: Option<String>,
+        #[arg(
+            long,
+            help = "Apply --orgs and --username to this GitHub host's [[hosts]] entry, adding it if needed"
+        )]
+        host: Option<String>,
+        #[arg(
+            long,
+            help = "Check with GitHub that gh is logged in as the username before saving"
+        )]
+        check: bool,
         #[arg(
             short = 'r',
             long = "repo-pattern",
-            help = "Regex pattern to filter repository names (use 'none' to clear)"
+            help = "Regex pattern to filter repository names; repeat for several alternatives (use 'none' to clear)"
         )]
-        repo_pattern: Option<String>,
+        repo_pattern: Vec<String>,
+        #[arg(
+            long = "exclude-repo",
+            value_name = "GLOB",
+            help = "Never scan repositories matching GLOB, e.g. '*-sandbox' or 'acme/*-test'; repeat for several, replacing the list (use 'none' to clear)"
+        )]
+        exclude_repos: Vec<String>,
     },
     #[command(about = "Show current configuration")]
-    Config,
+    Config {
+        #[arg(
+            long,
+            help = "Don't run 'gh auth status', just print the stored configuration"
+        )]
+        no_auth_check: bool,
+    },
+    #[command(about = "Manage the configured organizations")]
+    Orgs {
+        #[command(subcommand)]
+        command: OrgsCommand,
+    },
+    #[command(about = "Show which of the given repository names a pattern matches, offline")]
+    PatternMatch {
+        #[arg(help = "Regex pattern, as for --repo-pattern")]
+        pattern: String,
+        #[arg(required = true, help = "Repository names to try the pattern on")]
+        names: Vec<String>,
+    },
+    #[command(about = "Show review trends from the history recorded with --record-history")]
+    Stats,
+    #[command(
+        about = "Scan again only the repositories the last scan couldn't check, merging in its results"
+    )]
+    RetryFailed,
+    #[command(about = "Manage the cached repository lists")]
+    Cache {
+        #[command(subcommand)]
+        command: CacheCommand,
+    },
+    #[command(about = "Show version and build information")]
+    Version,
+    #[command(about = "Quick end-to-end check of gh, auth and API access, for monitoring")]
+    Healthcheck,
 }
 
-struct GitHubClient;
-
-impl GitHubClient {
-    fn new() -> Self {
-        Self
-    }
-
-    fn search_prs_for_user(
-        &self,
-        orgs: &[String],
-        username: &str,
-        repo_pattern: Option<&str>,
-    ) -> Result<Vec<PullRequest>> {
-        self.search_prs(orgs, username, false, repo_pattern)
-    }
-
-    fn search_own_prs(
-        &self,
-        orgs: &[String],
-        username: &str,
-        repo_pattern: Option<&str>,
-    ) -> Result<Vec<PullRequest>> {
-        self.search_prs(orgs, username, true, repo_pattern)
-    }
+#[derive(Subcommand, Debug)]
+enum OrgsCommand {
+    #[command(about = "Pick organizations from the ones you belong to and save them")]
+    Sync {
+        #[arg(
+            long,
+            help = "Select every organization you belong to instead of asking"
+        )]
+        all: bool,
+        #[arg(short, long, help = "Save the changes without asking for confirmation")]
+        yes: bool,
+    },
+}
 
-    fn search_prs(
-        &self,
-        orgs: &[String],
-        username: &str,
-        own_prs: bool,
-        repo_pattern: Option<&str>,
-    ) -> Result<Vec<PullRequest>> {
-        let mut all_repos = Vec::new();
-        let total_orgs = orgs.len();
+#[derive(Subcommand, Debug)]
+enum CacheCommand {
+    #[command(
+        about = "List every configured organization now and cache the lists, without scanning PRs (e.g. from cron)"
+    )]
+    Warm,
+}
 
-        println!(
-            "📡 Getting repositories from {} organization(s)...",
-            total_orgs
-        );
+/// Repositories a scan must cover without a single match before a login that
+/// has never matched is flagged as possibly wrong.
+const UNMATCHED_LOGIN_REPOS: usize = 20;
 
-        for (idx, org) in orgs.iter().enumerate() {
-            print!(
-                "\r🏛️  Fetching from {} ({}/{})...",
-                org,
-                idx + 1,
-                total_orgs
-            );
-            std::io::stdout().flush().unwrap();
+/// Set when stdout carries machine-readable results, so progress goes to stderr.
+static STATUS_TO_STDERR: AtomicBool = AtomicBool::new(false);
 
-            let repos_output = Command::new("gh")
-                .args(["repo", "list", org, "--json", "name", "--limit", "1000"])
-                .output()?;
+/// Set for the JSON formats, so errors are printed as `{"error": "..."}`.
+static JSON_ERRORS: AtomicBool = AtomicBool::new(false);
 
-            if !repos_output.status.success() {
-                eprintln!("\n⚠️  Failed to list repositories for {}, skipping...", org);
-                continue;
-            }
+/// Set by `--quiet` to drop progress output altogether.
+static QUIET: AtomicBool = AtomicBool::new(false);
 
-            let repos_stdout = String::from_utf8(repos_output.stdout)?;
-            let mut org_repos: Vec<GhRepo> = serde_json::from_str(&repos_stdout)?;
+/// Pause before retrying an auth check that failed on a network error.
+const AUTH_RETRY_DELAY: Duration = Duration::from_secs(1);
 
-            // Add org name to each repo for later reference
-            for repo in &mut org_repos {
-                repo.org = org.clone();
-            }
-            all_repos.extend(org_repos);
+/// Like `print!`, but for progress chatter that must stay off a machine-readable stdout.
+macro_rules! status {
+    ($($arg:tt)*) => {{
+        if QUIET.load(Ordering::Relaxed) {
+            // Dropped
+        } else if STATUS_TO_STDERR.load(Ordering::Relaxed) {
+            eprint!($($arg)*);
+            let _ = std::io::stderr().flush();
+        } else {
+            print!($($arg)*);
+            let _ = std::io::stdout().flush();
         }
+    }};
+}
 
-        println!(
-            "\r🏛️  Found {} total repositories across {} organization(s)",
-            all_repos.len(),
-            total_orgs
-        );
-
-        let repos = all_repos;
-
-        // Filter repositories if pattern is provided
-        let filtered_repos = if let Some(pattern) = repo_pattern {
-            let regex = Regex::new(pattern)
-                .map_err(|e| anyhow::anyhow!("Invalid regex pattern '{}': {}", pattern, e))?;
+macro_rules! statusln {
+    ($($arg:tt)*) => {{
+        status!($($arg)*);
+        status!("\n");
+    }};
+}
 
-            // Only keep repos that match the pattern
-            let matching: Vec<GhRepo> = repos
-                .into_iter()
-                .filter(|repo| regex.is_match(&repo.name))
-                .collect();
+/// One host's worth of orgs to scan, with the login to match on that host.
+struct ScanTarget {
+    host: Option<String>,
+    orgs: Vec<String>,
+    username: String,
+    /// Per-org logins that replace `username` when matching.
+    org_usernames: BTreeMap<String, String>,
+    /// Per-org repositories to scan instead of listing the org.
+    pinned_repos: BTreeMap<String, Vec<String>>,
+}
 
-            println!(
-                " found {} repositories matching pattern '{}'",
-                matching.len(),
-                pattern
-            );
-            matching
+fn main() {
+    let args = Args::parse();
+    // Until the config's default format is known, go by --format alone
+    JSON_ERRORS.store(
+        args.json || args.format.is_some_and(OutputFormat::is_json),
+        Ordering::Relaxed,
+    );
+    if let Err(e) = run(args) {
+        if JSON_ERRORS.load(Ordering::Relaxed) {
+            eprintln!("{}", serde_json::json!({ "error": format!("{:#}", e) }));
         } else {
-            println!(" found {} repositories", repos.len());
-            repos
-        };
-
-        let mut all_prs = Vec::new();
-        let mut checked_repos = 0;
-        let total_repos = filtered_repos.len();
-
-        // For each repository, get PRs
-        for repo in filtered_repos {
-            checked_repos += 1;
-            if checked_repos % 10 == 0 || checked_repos == 1 {
-                print!(
-                    "\r🔍 Checking repositories... {}/{}",
-                    checked_repos, total_repos
-                );
-                std::io::stdout().flush().unwrap();
-            }
-
-            let repo_name = format!("{}/{}", repo.org, repo.name);
-
-            let mut args = vec![
-                "pr",
-                "list",
-                "--repo",
-                &repo_name,
-                "--json",
-                "number,title,url,author,reviewRequests",
-                "--state",
-                "open",
-            ];
-
-            if own_prs {
-                args.extend(&["--author", username]);
-            }
-
-            let prs_output = Command::new("gh").args(&args).output()?;
-
-            if !prs_output.status.success() {
-                // Skip repos we can't access instead of failing
-                continue;
-            }
-
-            let prs_stdout = String::from_utf8(prs_output.stdout)?;
-            let prs: Vec<GhPullRequest> = serde_json::from_str(&prs_stdout).unwrap_or_default();
-
-            for pr in prs {
-                if own_prs {
-                    // For own PRs, just add all PRs by the user
-                    all_prs.push(PullRequest {
-                        number: pr.number,
-                        title: pr.title,
-                        html_url: pr.url,
-                        user: User {
-                            login: pr.author.login,
-                        },
-                    });
-                } else {
-                    // For review requests, filter PRs where the user is requested for review
-                    let is_requested = pr.review_requests.iter().any(|req| req.login == username);
-                    if is_requested {
-                        all_prs.push(PullRequest {
-                            number: pr.number,
-                            title: pr.title,
-                            html_url: pr.url,
-                            user: User {
-                                login: pr.author.login,
-                            },
-                        });
-                    }
-                }
-            }
+            eprintln!("Error: {:?}", e);
         }
-
-        print!("\r🔍 Checked {} repositories            \n", checked_repos);
-
-        Ok(all_prs)
+        std::process::exit(1);
     }
 }
 
-fn main() -> Result<()> {
-    let args = Args::parse();
+fn run(args: Args) -> Result<()> {
+    let token = match args
+        .token_file
+        .clone()
+        .or_else(|| std::env::var_os("REVIEW_RADAR_TOKEN_FILE").map(PathBuf::from))
+    {
+        Some(path) => Some(read_token_file(&path)?),
+        None => None,
+    };
 
+    let retrying = matches!(args.command, Some(Commands::RetryFailed));
     match args.command {
         Some(Commands::Init {
             orgs,
             username,
             repo_pattern,
+            interactive,
         }) => {
-            let org_list: Vec<String> = orgs.split(',').map(|s| s.trim().to_string()).collect();
+            let (orgs, username) = match (orgs, username) {
+                (Some(orgs), Some(username)) => (orgs, username),
+                (None, None)
+                    if interactive
+                        || (repo_pattern.is_none() && std::io::stdin().is_terminal()) =>
+                {
+                    return init_interactive(token);
+                }
+                _ => {
+                    return Err(anyhow::anyhow!(
+                        "Pass both the organizations and your username (e.g. 'rr init my-org my-login'), or run 'rr init --interactive'"
+                    ))
+                }
+            };
+            let org_list: Vec<String> =
+                dedup_orgs(orgs.split(',').map(|s| s.trim().to_string()).collect()).0;
             let config = Config {
                 orgs: org_list.clone(),
                 username,
                 repo_pattern,
+                ..Default::default()
             };
             config.save()?;
             println!("✅ Configuration saved successfully!");
             println!("📋 Organizations: {}", org_list.join(", "));
-            if config.repo_pattern.is_some() {
-                println!(
-                    "📋 Repository filter pattern: {}",
-                    config.repo_pattern.as_ref().unwrap()
-                );
+            if let Some(pattern) = &config.repo_pattern {
+                println!("📋 Repository filter pattern: {}", pattern);
             }
             println!("💡 Make sure you're authenticated with GitHub CLI: gh auth status");
             return Ok(());
@@ -274,44 +614,108 @@ fn main() -> Result<()> {
         Some(Commands::Set {
             orgs,
             username,
+            host,
+            check,
             repo_pattern,
+            exclude_repos,
         }) => {
             let mut config = Config::load()?;
             let mut updated = false;
 
-            if let Some(org_str) = orgs {
-                match parse_org_modification(&org_str) {
-                    OrgModification::Add(new_org) => {
-                        if config.add_org(new_org.clone()) {
-                            println!("➕ Added organization: {}", new_org);
-                            updated = true;
-                        } else {
-                            println!("ℹ️  Organization '{}' already exists", new_org);
-                        }
-                    }
-                    OrgModification::Remove(remove_org) => {
-                        if config.remove_org(&remove_org) {
-                            println!("➖ Removed organization: {}", remove_org);
-                            updated = true;
-                        } else {
-                            println!("ℹ️  Organization '{}' not found", remove_org);
-                        }
+            let org_change = orgs.map(|org_str| parse_org_modification(&org_str));
+            match (&host, org_change) {
+                (_, None) => {}
+                (None, Some(OrgModification::Add(new_org))) => {
+                    if config.add_org(new_org.clone()) {
+                        println!("➕ Added organization: {}", new_org);
+                        updated = true;
+                    } else {
+                        println!("ℹ️  Organization '{}' already exists", new_org);
                     }
-                    OrgModification::Replace(new_orgs) => {
-                        config.set_orgs(new_orgs);
-                        println!("✅ Updated organizations");
+                }
+                (None, Some(OrgModification::Remove(remove_org))) => {
+                    if config.remove_org(&remove_org) {
+                        println!("➖ Removed organization: {}", remove_org);
                         updated = true;
+                    } else {
+                        println!("ℹ️  Organization '{}' not found", remove_org);
+                    }
+                }
+                (None, Some(OrgModification::Replace(new_orgs))) => {
+                    config.set_orgs(new_orgs);
+                    println!("✅ Updated organizations");
+                    updated = true;
+                }
+                (Some(host), Some(change)) => {
+                    let entry = config.host_mut(host);
+                    match change {
+                        OrgModification::Add(new_org) => {
+                            if !entry.orgs.contains(&new_org) {
+                                entry.orgs.push(new_org);
+                            }
+                        }
+                        OrgModification::Remove(remove_org) => {
+                            entry.orgs.retain(|org| *org != remove_org)
+                        }
+                        OrgModification::Replace(new_orgs) => entry.orgs = dedup_orgs(new_orgs).0,
                     }
+                    println!("✅ Updated organizations on {}", host);
+                    updated = true;
                 }
             }
             if let Some(new_username) = username {
-                config.username = new_username;
+                match &host {
+                    Some(host) => config.host_mut(host).username = new_username,
+                    None => config.username = new_username,
+                }
                 updated = true;
             }
-            if let Some(new_pattern) = repo_pattern {
-                match config.set_repo_pattern(Some(new_pattern)) {
+            if check {
+                let username = match &host {
+                    Some(host) => config
+                        .hosts
+                        .iter()
+                        .find(|entry| entry.host == *host)
+                        .map(|entry| entry.username.clone())
+                        .ok_or_else(|| {
+                            anyhow::anyhow!("There is no [[hosts]] entry for {} to check", host)
+                        })?,
+                    None => config.username.clone(),
+                };
+                let host_name = host.as_deref().unwrap_or("github.com");
+                let login = GitHubClient::new(host.clone(), ScanOptions::default())
+                    .with_token(token.clone())
+                    .authenticated_login()
+                    .map_err(|e| {
+                        anyhow::anyhow!(
+                            "Could not check the account on {} ({}); log in with 'gh auth login --hostname {}', or leave out --check. Nothing was saved",
+                            host_name,
+                            e,
+                            host_name
+                        )
+                    })?;
+                if username != "@me" && !username.eq_ignore_ascii_case(&login) {
+                    return Err(anyhow::anyhow!(
+                        "gh is logged in to {} as '{}', not '{}'; nothing was saved",
+                        host_name,
+                        login,
+                        username
+                    ));
+                }
+                // Scans compare logins exactly, so keep GitHub's spelling
+                if username != "@me" && username != login {
+                    match &host {
+                        Some(host) => config.host_mut(host).username = login.clone(),
+                        None => config.username = login.clone(),
+                    }
+                    updated = true;
+                }
+                println!("🔐 Logged in to {} as {}", host_name, login);
+            }
+            if !repo_pattern.is_empty() {
+                match config.set_repo_patterns(repo_pattern) {
                     Ok(_) => {
-                        if config.repo_pattern.is_none() {
+                        if config.effective_repo_pattern().is_none() {
                             println!("🗑️  Cleared repository filter pattern");
                         } else {
                             println!("✅ Updated repository filter pattern");
@@ -324,6 +728,22 @@ fn main() -> Result<()> {
                     }
                 }
             }
+            if !exclude_repos.is_empty() {
+                match config.set_exclude_repos(exclude_repos) {
+                    Ok(_) => {
+                        if config.exclude_repos.is_empty() {
+                            println!("🗑️  Cleared excluded repositories");
+                        } else {
+                            println!("✅ Updated excluded repositories");
+                        }
+                        updated = true;
+                    }
+                    Err(e) => {
+                        println!("❌ {}", e);
+                        return Ok(());
+                    }
+                }
+            }
 
             if updated {
                 config.save()?;
@@ -333,7 +753,7 @@ fn main() -> Result<()> {
             }
             return Ok(());
         }
-        Some(Commands::Config) => {
+        Some(Commands::Config { no_auth_check }) => {
             match Config::load() {
                 Ok(config) => {
                     println!("Current configuration:");
@@ -341,118 +761,1513 @@ fn main() -> Result<()> {
                     println!("  Username: {}", config.username);
                     if let Some(pattern) = &config.repo_pattern {
                         println!("  Repository filter: {}", pattern);
+                    } else if !config.repo_patterns.is_empty() {
+                        println!(
+                            "  Repository filters (any may match): {}",
+                            config.repo_patterns.join(", ")
+                        );
                     } else {
                         println!("  Repository filter: (none)");
                     }
-
-                    // Check gh auth status
-                    let output = Command::new("gh").args(["auth", "status"]).output();
-                    match output {
-                        Ok(output) if output.status.success() => {
-                            println!("  GitHub CLI: ✅ Authenticated");
-                        }
-                        _ => {
-                            println!("  GitHub CLI: ❌ Not authenticated (run 'gh auth login')");
-                        }
+                    if !config.exclude_repos.is_empty() {
+                        println!(
+                            "  Excluded repositories: {}",
+                            config.exclude_repos.join(", ")
+                        );
                     }
-                }
+                    for (org, hours) in &config.review_sla_hours {
+                        println!("  Review SLA for {}: {}h", org, hours);
+                    }
+                    if let Some(format) = config.defaults.format {
+                        println!(
+                            "  Default format: {}",
+                            format.to_possible_value().unwrap().get_name()
+                        );
+                    }
+                    if config.defaults.no_pager {
+                        println!("  Default: --no-pager");
+                    }
+                    if config.defaults.legacy_scan {
+                        println!("  Default: --legacy-scan");
+                    }
+                    if let Some(mode) = config.defaults.mode {
+                        println!(
+                            "  Default mode: {}",
+                            mode.to_possible_value().unwrap().get_name()
+                        );
+                    }
+                    if let Some(limit) = config.defaults.pr_limit {
+                        println!("  Default PR limit: {}", limit);
+                    }
+                    if let Some(sort) = config.defaults.sort {
+                        println!(
+                            "  Default sort: {}",
+                            sort.to_possible_value().unwrap().get_name()
+                        );
+                    }
+                    if let Some(concurrency) = config.defaults.concurrency {
+                        println!("  Default concurrency: {}", concurrency);
+                    }
+                    for (org, alias) in &config.org_usernames {
+                        println!("  Username in {}: {}", org, alias);
+                    }
+                    for (org, name) in &config.org_names {
+                        println!("  Name of {}: {}", org, name);
+                    }
+                    for (org, repos) in &config.repos {
+                        println!("  Repositories in {}: {}", org, repos.join(", "));
+                    }
+                    for host in &config.hosts {
+                        println!(
+                            "  Host {}: {} (as {})",
+                            host.host,
+                            host.orgs.join(", "),
+                            host.username
+                        );
+                    }
+
+                    // Check gh auth status
+                    if !no_auth_check {
+                        let output = gh_command(None, token.as_deref())
+                            .args(["auth", "status"])
+                            .output();
+                        match output {
+                            Ok(output) if output.status.success() => {
+                                println!("  GitHub CLI: ✅ Authenticated");
+                            }
+                            _ => {
+                                println!(
+                                    "  GitHub CLI: ❌ Not authenticated (run 'gh auth login')"
+                                );
+                            }
+                        }
+                    }
+                }
                 Err(e) => {
                     println!("❌ {}", e);
                 }
             }
             return Ok(());
         }
-        None => {}
-    }
+        Some(Commands::Orgs {
+            command: OrgsCommand::Sync { all, yes },
+        }) => {
+            return sync_orgs(token, all, yes);
+        }
+        Some(Commands::PatternMatch { pattern, names }) => {
+            let regex = compile_repo_pattern(&pattern)?;
+            let mut matched = 0;
+            for name in &names {
+                if regex.is_match(name) {
+                    matched += 1;
+                    println!("✅ {}", name);
+                } else {
+                    println!("❌ {}", name);
+                }
+            }
+            println!(
+                "📊 {} of {} names match '{}'",
+                matched,
+                names.len(),
+                pattern
+            );
+            return Ok(());
+        }
+        Some(Commands::Healthcheck) => {
+            // One line on stdout and the exit code are all a probe looks at
+            let result = Config::load().and_then(|config| {
+                if !check_auth(token.as_deref())? {
+                    return Err(anyhow::anyhow!("gh is not authenticated"));
+                }
+                let status = GitHubClient::new(None, ScanOptions::default())
+                    .with_token(token.clone())
+                    .healthcheck(&config.orgs)?;
+                // A suspended or deleted login scans fine but never matches anything;
+                // a lookup that fails for another reason isn't a verdict, so it's skipped
+                let logins = std::iter::once((None, &config.username)).chain(
+                    config
+                        .hosts
+                        .iter()
+                        .map(|host| (Some(host.host.clone()), &host.username)),
+                );
+                for (host, login) in logins.filter(|(_, login)| *login != "@me") {
+                    let client =
+                        GitHubClient::new(host, ScanOptions::default()).with_token(token.clone());
+                    if let Ok(Some(problem)) = client.account_problem(login) {
+                        return Err(anyhow::anyhow!("{}", problem));
+                    }
+                }
+                Ok(status)
+            });
+            match result {
+                Ok(status) => println!("OK: gh authenticated, {}", status),
+                Err(e) => {
+                    println!("FAIL: {}", e);
+                    std::process::exit(1);
+                }
+            }
+            return Ok(());
+        }
+        Some(Commands::Cache {
+            command: CacheCommand::Warm,
+        }) => {
+            let config = Config::load()?;
+            let mut targets = vec![(None, config.orgs.clone(), config.repos.clone())];
+            targets.extend(
+                config
+                    .hosts
+                    .iter()
+                    .map(|host| (Some(host.host.clone()), host.orgs.clone(), BTreeMap::new())),
+            );
+            let mut failed = Vec::new();
+            for (host, orgs, pinned_repos) in targets {
+                if orgs.is_empty() {
+                    continue;
+                }
+                let client = GitHubClient::new(host.clone(), ScanOptions::default())
+                    .with_org_names(config.org_names.clone())
+                    .with_pinned_repos(pinned_repos)
+                    .with_token(token.clone())
+                    .with_reporter(Box::new(StatusReporter));
+                let (cached, host_failed) = client.warm_repo_cache(&orgs)?;
+                println!(
+                    "🔥 Cached {} repositories{}",
+                    cached,
+                    host.map(|host| format!(" on {}", host)).unwrap_or_default()
+                );
+                failed.extend(host_failed);
+            }
+            if !failed.is_empty() {
+                return Err(anyhow::anyhow!(
+                    "Couldn't list the repositories of {}",
+                    failed.join(", ")
+                ));
+            }
+            return Ok(());
+        }
+        Some(Commands::Stats) => {
+            let path = History::path()?;
+            match history_stats(&History::load_from_path(&path)?) {
+                Some(stats) => print!("{}", format::render_stats(&stats)),
+                None => println!(
+                    "ℹ️  No history recorded yet: run 'rr --record-history' regularly (e.g. from cron)"
+                ),
+            }
+            return Ok(());
+        }
+        Some(Commands::Version) => {
+            println!("rr {}", env!("CARGO_PKG_VERSION"));
+            println!("  Git commit: {}", env!("RR_GIT_COMMIT"));
+            println!("  Build date: {}", env!("RR_BUILD_DATE"));
 
-    // Check if gh is authenticated before proceeding
-    let auth_output = Command::new("gh").args(["auth", "status"]).output()?;
-    if !auth_output.status.success() {
-        println!("❌ GitHub CLI is not authenticated. Run 'gh auth login' first.");
-        return Ok(());
+            let output = Command::new("gh").arg("--version").output();
+            match output {
+                Ok(output) if output.status.success() => {
+                    let version = String::from_utf8_lossy(&output.stdout);
+                    let first_line = version.lines().next().unwrap_or_default();
+                    println!("  GitHub CLI: {}", first_line);
+                }
+                _ => println!("  GitHub CLI: ❌ Not found"),
+            }
+            return Ok(());
+        }
+        Some(Commands::RetryFailed) | None => {}
     }
 
     let config = Config::load()?;
 
+    let last_scan = if retrying {
+        if args.query.is_some()
+            || args.repo.is_some()
+            || args.orgs.is_some()
+            || !args.for_users.is_empty()
+            || args.own_prs
+            || args.mode.is_some()
+        {
+            return Err(anyhow::anyhow!(
+                "retry-failed repeats the last scan on the repositories it couldn't check, so it can't be combined with --query, a repository, --orgs, --for, --own or --mode"
+            ));
+        }
+        Some(
+            LastScan::load_from_path(&LastScan::path()?)?.ok_or_else(|| {
+                anyhow::anyhow!(
+                    "The last scan checked every repository, so there is nothing to retry"
+                )
+            })?,
+        )
+    } else {
+        None
+    };
+
+    // Flags given on the command line win over the config's [defaults]
+    let sort = args.sort.or(config.defaults.sort);
+    let output_format = if args.json {
+        OutputFormat::Json
+    } else {
+        args.format.or(config.defaults.format).unwrap_or_default()
+    };
+    // Every format rendered this run, so the scan fetches what each one needs
+    let formats: Vec<OutputFormat> = std::iter::once(output_format)
+        .chain(args.outputs.iter().map(|(_, format)| *format))
+        .collect();
+    let no_pager = args.no_pager || config.defaults.no_pager;
+    let mode = if let Some(last) = &last_scan {
+        last.mode
+    } else if args.own_prs {
+        SearchMode::Own
+    } else if !args.for_users.is_empty() {
+        SearchMode::Review
+    } else {
+        args.mode.or(config.defaults.mode).unwrap_or_default()
+    };
+    if mode != SearchMode::Review && args.record_history {
+        return Err(anyhow::anyhow!(
+            "--record-history records your review queue, but the search mode is '{}'",
+            mode.to_possible_value().unwrap().get_name()
+        ));
+    }
+    if matches!(mode, SearchMode::Own | SearchMode::Inbox)
+        && (args.team_only || args.include_closed_requests)
+    {
+        return Err(anyhow::anyhow!(
+            "--team-only and --include-closed-requests only apply to review requests, but the search mode is '{}'",
+            mode.to_possible_value().unwrap().get_name()
+        ));
+    }
+    if formats.contains(&OutputFormat::RawGh) && args.query.is_some() {
+        return Err(anyhow::anyhow!(
+            "--format raw-gh prints what 'gh pr list' reports, so it can't be used with --query"
+        ));
+    }
+    STATUS_TO_STDERR.store(output_format.is_machine_readable(), Ordering::Relaxed);
+    JSON_ERRORS.store(output_format.is_json(), Ordering::Relaxed);
+    QUIET.store(
+        args.quiet || output_format == OutputFormat::None,
+        Ordering::Relaxed,
+    );
+
+    // Bad title patterns, like a bad baseline below, should fail before a long scan
+    let title_pattern = args
+        .title_pattern
+        .as_deref()
+        .map(compile_repo_pattern)
+        .transpose()?;
+    let exclude_title_pattern = args
+        .exclude_title_pattern
+        .as_deref()
+        .map(compile_repo_pattern)
+        .transpose()?;
+
+    // Like the title patterns, a bad project should fail before a long scan
+    let project = match &args.project {
+        Some(spec) => {
+            let default_owner = match config.orgs.as_slice() {
+                [only] => Some(only.as_str()),
+                _ => None,
+            };
+            Some(parse_project(spec, default_owner)?)
+        }
+        None => None,
+    };
+
+    // Read the baseline up front so a bad path fails before a long scan
+    let baseline = match &args.baseline {
+        Some(path) => Some(format::read_snapshot(path)?),
+        None if formats.contains(&OutputFormat::Delta) => {
+            return Err(anyhow::anyhow!(
+                "The delta format needs a snapshot to compare against: pass --baseline <FILE>"
+            ));
+        }
+        None => None,
+    };
+
     // Use command-line orgs if provided, otherwise use config orgs
-    let orgs = if let Some(org_str) = args.orgs {
-        org_str.split(',').map(|s| s.trim().to_string()).collect()
+    let orgs: Vec<String> = if let Some(org_str) = &args.orgs {
+        let requested: Vec<String> = org_str.split(',').map(|s| s.trim().to_string()).collect();
+        expand_orgs(requested, token.as_deref(), args.page_size)?
     } else {
         config.orgs.clone()
     };
+    let (orgs, duplicates) = dedup_orgs(orgs);
+    if !duplicates.is_empty() {
+        eprintln!(
+            "ℹ️  Scanning each organization once, ignoring repeats of: {}",
+            duplicates.join(", ")
+        );
+    }
+    let username = args.username.as_ref().unwrap_or(&config.username);
 
-    if orgs.is_empty() {
+    // The default host, plus any extra configured hosts unless orgs were overridden
+    let mut targets = Vec::new();
+    if !orgs.is_empty() {
+        targets.push(ScanTarget {
+            host: None,
+            orgs,
+            username: username.clone(),
+            // An explicit --username or --for is meant for every org
+            org_usernames: if args.username.is_some() || !args.for_users.is_empty() {
+                BTreeMap::new()
+            } else {
+                config.org_usernames.clone()
+            },
+            pinned_repos: config.repos.clone(),
+        });
+    }
+    // Other hosts have their own logins, so --for only reviews the default one
+    if args.orgs.is_none() && args.for_users.is_empty() {
+        targets.extend(
+            config
+                .hosts
+                .iter()
+                .filter(|host| !host.orgs.is_empty())
+                .map(|host| ScanTarget {
+                    host: Some(host.host.clone()),
+                    orgs: host.orgs.clone(),
+                    username: host.username.clone(),
+                    org_usernames: BTreeMap::new(),
+                    pinned_repos: BTreeMap::new(),
+                }),
+        );
+    }
+
+    if let Some(repo) = &args.repo {
+        let (org, name) = repo
+            .split_once('/')
+            .filter(|(org, name)| !org.is_empty() && !name.is_empty() && !name.contains('/'))
+            .ok_or_else(|| {
+                anyhow::anyhow!("'{}' is not a repository: use the OWNER/REPO form", repo)
+            })?;
+        // One repository on the default host, pinned so the org isn't listed
+        targets = vec![ScanTarget {
+            host: None,
+            orgs: vec![org.to_string()],
+            username: username.clone(),
+            org_usernames: if args.username.is_some() {
+                BTreeMap::new()
+            } else {
+                config.org_usernames.clone()
+            },
+            pinned_repos: BTreeMap::from([(org.to_string(), vec![name.to_string()])]),
+        }];
+    }
+
+    if let Some(last) = &last_scan {
+        // The failed repositories, pinned per host and login so no org is listed
+        let mut pinned: BTreeMap<(Option<String>, String), BTreeMap<String, Vec<String>>> =
+            BTreeMap::new();
+        for failed in &last.failed {
+            if let Some((org, name)) = failed.repo.split_once('/') {
+                let names = pinned
+                    .entry((failed.host.clone(), failed.username.clone()))
+                    .or_default()
+                    .entry(org.to_string())
+                    .or_default();
+                if !names.iter().any(|known| known == name) {
+                    names.push(name.to_string());
+                }
+            }
+        }
+        targets = pinned
+            .into_iter()
+            .map(|((host, username), pinned_repos)| ScanTarget {
+                org_usernames: if host.is_none() {
+                    config.org_usernames.clone()
+                } else {
+                    BTreeMap::new()
+                },
+                host,
+                orgs: pinned_repos.keys().cloned().collect(),
+                username,
+                pinned_repos,
+            })
+            .collect();
+        statusln!(
+            "🔁 Retrying the {} repositories the last scan couldn't check",
+            last.failed.len()
+        );
+    }
+
+    for skipped in &args.skip_orgs {
+        if !targets.iter().any(|target| target.orgs.contains(skipped)) {
+            eprintln!(
+                "ℹ️  --skip-org {}: not among the organizations to scan",
+                skipped
+            );
+        }
+    }
+    for target in &mut targets {
+        target.orgs.retain(|org| !args.skip_orgs.contains(org));
+    }
+    targets.retain(|target| !target.orgs.is_empty());
+
+    let config_pattern = config.effective_repo_pattern();
+    if args.query.is_some() {
+        // A raw query brings its own scope
+        targets.clear();
+    } else if targets.is_empty() {
+        // A pattern only narrows down the repositories of some org, so call that out
+        if let Some(pattern) = args.repo_pattern.as_ref().or(config_pattern.as_ref()) {
+            return Err(anyhow::anyhow!(
+                "The repository pattern '{}' needs at least one organization to search in. Use 'rr set --orgs' or pass --orgs.",
+                pattern
+            ));
+        }
         return Err(anyhow::anyhow!(
             "No organizations configured. Use 'rr init' or 'rr set --orgs' to configure."
         ));
     }
 
-    let username = args.username.as_ref().unwrap_or(&config.username);
-
-    let client = GitHubClient::new();
+    // Check if gh is authenticated before proceeding
+    if !check_auth(token.as_deref())? {
+        // Succeeding here would read as "PRs found" to an exit-code-only caller
+        if output_format == OutputFormat::None {
+            return Err(anyhow::anyhow!(
+                "GitHub CLI is not authenticated. Run 'gh auth login' first."
+            ));
+        }
+        println!("❌ GitHub CLI is not authenticated. Run 'gh auth login' first.");
+        return Ok(());
+    }
 
-    // Use command-line pattern if provided, otherwise use config pattern
+    // Use command-line pattern if provided, otherwise use config pattern. A
+    // single named repository is scanned regardless.
     let repo_pattern = args
         .repo_pattern
         .as_deref()
-        .or(config.repo_pattern.as_deref());
+        .or(config_pattern.as_deref())
+        .filter(|_| args.repo.is_none() && last_scan.is_none());
 
-    let (prs, search_type) = if args.own_prs {
+    let options = ScanOptions {
+        resume: args.resume,
+        conflicts_only: args.conflicts_only,
+        team_only: args.team_only,
+        visibility: args.visibility,
+        yes: args.yes,
+        fields: PrFields {
+            review_decision: formats.contains(&OutputFormat::Kanban),
+            raw: formats.contains(&OutputFormat::RawGh),
+            created_at: args.notify_summary
+                || args.record_history
+                || sort == Some(SortKey::Age)
+                || args.discord_webhook.is_some()
+                || formats.iter().any(|format| {
+                    matches!(
+                        format,
+                        OutputFormat::Text
+                            | OutputFormat::SummaryOnly
+                            | OutputFormat::Obsidian
+                            | OutputFormat::Mermaid
+                            | OutputFormat::Discord
+                    )
+                }),
+            ..Default::default()
+        },
+        refresh_repos: args.refresh_repos,
+        include_closed: args.include_closed_requests,
+        full_parse: args.no_jq,
+        pr_limit: args.pr_limit.or(config.defaults.pr_limit),
+        gh_args: args.gh_args.clone(),
+        strict: args.strict,
+        explain: args.explain,
+        min_permission: args.min_permission,
+        progress: args.progress,
+        page_size: args.page_size,
+        concurrency: args
+            .concurrency
+            .or(config.defaults.concurrency)
+            .unwrap_or(DEFAULT_CONCURRENCY)
+            .max(1),
+        legacy_scan: args.legacy_scan || config.defaults.legacy_scan,
+        // A single named repository is scanned regardless, as with patterns
+        exclude_repos: if args.repo.is_some() || last_scan.is_some() {
+            Vec::new()
+        } else {
+            config.exclude_repo_globs()?
+        },
+        active_since: args
+            .repos_active_since
+            .as_deref()
+            .map(parse_date)
+            .transpose()?,
+        sample: args.sample.map(|count| {
+            // Without an explicit seed pick a fresh one; it is printed so the run can be repeated
+            let seed = args.seed.unwrap_or_else(|| {
+                SystemTime::now()
+                    .duration_since(UNIX_EPOCH)
+                    .map(|d| d.as_nanos() as u64)
+                    .unwrap_or_default()
+            });
+            (count, seed)
+        }),
+    };
+
+    let mut result = SearchResult::default();
+    let mut scanned_targets = Vec::new();
+    let mut reviewers = Vec::new();
+    // Each login's review scan: what it covered, if enough to tell a wrong
+    // login, and whether anything matched
+    let mut review_scans: Vec<(String, Option<String>, bool)> = Vec::new();
+    let match_history_path = MatchHistory::path()?;
+    let mut match_history = MatchHistory::load_from_path(&match_history_path);
+    let mut failed = Vec::new();
+    if let Some(query) = &args.query {
+        let client = GitHubClient::new(None, options.clone())
+            .with_token(token.clone())
+            .with_reporter(Box::new(StatusReporter));
+        statusln!("🔍 Searching for PRs matching '{}'...", query);
+        result = client.search_query(query)?;
+    }
+    for target in &targets {
+        let client = GitHubClient::new(target.host.clone(), options.clone())
+            .with_org_usernames(target.org_usernames.clone())
+            .with_org_names(config.org_names.clone())
+            .with_pinned_repos(target.pinned_repos.clone())
+            .with_token(token.clone())
+            .with_reporter(Box::new(StatusReporter));
+        // --for looks at other people's queues instead of your own
+        let usernames = if args.for_users.is_empty() {
+            vec![client.resolve_username(&target.username, args.refresh_identity)?]
+        } else {
+            args.for_users
+                .iter()
+                .map(|login| client.resolve_username(login, args.refresh_identity))
+                .collect::<Result<Vec<_>>>()?
+        };
+        let orgs = &target.orgs;
         let org_list = if orgs.len() > 2 {
             format!("{} organizations", orgs.len())
         } else {
-            orgs.join(", ")
+            orgs.iter()
+                .map(|org| client.org_name(org))
+                .collect::<Vec<_>>()
+                .join(", ")
+        };
+        let org_list = match &target.host {
+            Some(host) => format!("{} on {}", org_list, host),
+            None => org_list,
+        };
+
+        for username in &usernames {
+            scanned_targets.push(TargetMetadata {
+                host: target.host.clone(),
+                orgs: target.orgs.clone(),
+                username: username.clone(),
+            });
+            if args.verbose {
+                for org in &target.orgs {
+                    statusln!(
+                        "👤 Matching as {} in {}",
+                        client.username_for(org, username),
+                        client.org_name(org)
+                    );
+                }
+            }
+
+            if mode.includes_own() {
+                let search_desc = if let Some(pattern) = repo_pattern {
+                    format!(
+                        "🔍 Searching for {}'s open PRs in {} (repos matching '{}')...",
+                        username, org_list, pattern
+                    )
+                } else {
+                    format!(
+                        "🔍 Searching for {}'s open PRs in {}...",
+                        username, org_list
+                    )
+                };
+                statusln!("{}", search_desc);
+                let found = client.search_own_prs(orgs, username, repo_pattern)?;
+                record_failed(&mut failed, &found, &target.host, username);
+                result.merge(found);
+            }
+            if mode.includes_review() {
+                let search_desc = if let Some(pattern) = repo_pattern {
+                    format!("🔍 Searching for PRs in {} where {} has been requested for review (repos matching '{}')...", org_list, username, pattern)
+                } else {
+                    format!(
+                        "🔍 Searching for PRs in {} where {} has been requested for review...",
+                        org_list, username
+                    )
+                };
+                statusln!("{}", search_desc);
+                let mut found = client.search_prs_for_user(orgs, username, repo_pattern)?;
+                record_failed(&mut failed, &found, &target.host, username);
+                let mut matched = !found.prs.is_empty();
+                let covered = if found.searches > 0 && found.repos_scanned == 0 {
+                    // A search covers every repository, so look past the open
+                    // PRs before calling the login wrong
+                    if !matched && !match_history.matched.contains(username.as_str()) {
+                        matched = client.ever_review_requested(orgs, username)?;
+                    }
+                    Some(format!("any PR in {}", org_list))
+                } else {
+                    (found.repos_scanned >= UNMATCHED_LOGIN_REPOS)
+                        .then(|| format!("the {} repositories scanned", found.repos_scanned))
+                };
+                review_scans.push((username.clone(), covered, matched));
+                if !args.for_users.is_empty() {
+                    for pr in &mut found.prs {
+                        pr.requested_reviewer = Some(username.clone());
+                    }
+                }
+                result.merge(found);
+            }
+            if mode == SearchMode::Inbox {
+                let search_desc = if let Some(pattern) = repo_pattern {
+                    format!("🔍 Searching for PRs in {} that request review from, are assigned to or mention {} (repos matching '{}')...", org_list, username, pattern)
+                } else {
+                    format!(
+                        "🔍 Searching for PRs in {} that request review from, are assigned to or mention {}...",
+                        org_list, username
+                    )
+                };
+                statusln!("{}", search_desc);
+                let found = client.search_inbox(orgs, username, repo_pattern)?;
+                record_failed(&mut failed, &found, &target.host, username);
+                result.merge(found);
+            }
+        }
+        reviewers.extend(usernames);
+    }
+    if args.query.is_none() {
+        let found = match mode {
+            SearchMode::Review => "review requests",
+            SearchMode::Own => "your open PRs",
+            SearchMode::All => "review requests or your open PRs",
+            SearchMode::Inbox => "PRs that need you",
         };
-        let search_desc = if let Some(pattern) = repo_pattern {
-            format!(
-                "🔍 Searching for {}'s open PRs in {} (repos matching '{}')...",
-                username, org_list, pattern
-            )
+        // Searches don't go through repositories, so there is no total to give
+        if result.repos_scanned == 0 && result.searches > 0 {
+            statusln!("📊 {} repositories had {}", result.repos_with_prs, found);
+        } else {
+            statusln!(
+                "📊 {} of {} repositories had {}",
+                result.repos_with_prs,
+                result.repos_scanned,
+                found
+            );
+        }
+    }
+    if !review_scans.is_empty() {
+        let mut updated = false;
+        for (username, covered, matched) in &review_scans {
+            if *matched {
+                updated |= match_history.matched.insert(username.clone());
+            } else if let (Some(covered), false) =
+                (covered, match_history.matched.contains(username))
+            {
+                statusln!(
+                    "⚠️  No review has ever been requested from '{}' in {}: is that the right GitHub login? 'gh api user --jq .login' shows yours, and 'rr set --username @me' always uses it",
+                    username,
+                    covered
+                );
+            }
+        }
+        if updated {
+            match_history.save_to_path(&match_history_path)?;
+        }
+    }
+    if !result.repos_unparsed.is_empty() && !args.verbose {
+        statusln!(
+            "⚠️  Couldn't parse the PR list of {} repositories, so their PRs are missing (use --verbose for details)",
+            result.repos_unparsed.len()
+        );
+    }
+    if args.verbose {
+        if result.repos_scanned > 0 || result.searches == 0 {
+            statusln!(
+                "⏱️  Listed repositories in {:.1}s, scanned {} in {:.1}s",
+                result.listing_time.as_secs_f64(),
+                result.repos_scanned,
+                result.scanning_time.as_secs_f64()
+            );
+        }
+        if result.searches > 0 {
+            statusln!(
+                "⏱️  Ran {} GitHub search{} in {:.1}s, finding PRs in {} repositories",
+                result.searches,
+                if result.searches == 1 { "" } else { "es" },
+                result.search_time.as_secs_f64(),
+                result.repos_with_prs
+            );
+        }
+        if !result.repos_skipped.is_empty() {
+            statusln!(
+                "⚠️  Skipped {} inaccessible repositories: {}",
+                result.repos_skipped.len(),
+                result.repos_skipped.join(", ")
+            );
+            statusln!("💡 'rr retry-failed' checks just those again");
+        }
+        for (repo, error) in &result.repos_unparsed {
+            statusln!(
+                "⚠️  Couldn't parse the PR list of {}, so its PRs are missing: {}",
+                repo,
+                error
+            );
+        }
+        for (org, count) in &result.org_counts {
+            let name = config.org_names.get(org).unwrap_or(org);
+            statusln!("📊 {}: {} PR(s)", name, count);
+        }
+    }
+    let mut prs = result.prs;
+    if args.query.is_none() {
+        let retried = last_scan.is_some();
+        if let Some(last) = last_scan {
+            prs = last.with_retried(prs);
+        }
+        let path = LastScan::path()?;
+        if failed.is_empty() {
+            LastScan::clear_at_path(&path)?;
         } else {
-            format!(
-                "🔍 Searching for {}'s open PRs in {}...",
-                username, org_list
-            )
+            if retried {
+                statusln!(
+                    "⚠️  {} repositories still couldn't be checked",
+                    failed.len()
+                );
+            }
+            LastScan {
+                mode,
+                prs: prs.clone(),
+                failed,
+            }
+            .save_to_path(&path)?;
+        }
+    }
+    prs.retain(|pr| {
+        title_pattern
+            .as_ref()
+            .is_none_or(|re| re.is_match(&pr.title))
+            && !exclude_title_pattern
+                .as_ref()
+                .is_some_and(|re| re.is_match(&pr.title))
+    });
+    if let Some((owner, number)) = &project {
+        let urls = GitHubClient::new(None, options.clone())
+            .with_token(token.clone())
+            .project_pr_urls(owner, *number)?;
+        let before = prs.len();
+        prs.retain(|pr| urls.contains(&pr.html_url));
+        statusln!(
+            "📌 {} of {} PRs are on project {} of {}",
+            prs.len(),
+            before,
+            number,
+            owner
+        );
+    }
+    if args.explain {
+        for pr in &mut prs {
+            if let Some(re) = &title_pattern {
+                pr.explanation
+                    .push(format!("title matched '{}'", re.as_str()));
+            }
+            if let Some((owner, number)) = &project {
+                pr.explanation
+                    .push(format!("on project {} of {}", number, owner));
+            }
+            if let Some(re) = &exclude_title_pattern {
+                pr.explanation
+                    .push(format!("title didn't match '{}'", re.as_str()));
+            }
+        }
+    }
+
+    if !config.review_sla_hours.is_empty() {
+        for pr in &mut prs {
+            let org = pr.repo.split('/').next().unwrap_or_default();
+            pr.sla_hours = config.review_sla_hours.get(org).copied();
+        }
+    }
+
+    if let Some(key) = sort {
+        sort_prs(&mut prs, key);
+    } else if mode == SearchMode::Inbox && args.query.is_none() {
+        sort_inbox(&mut prs);
+    }
+
+    let for_others = if args.for_users.is_empty() {
+        None
+    } else {
+        Some(reviewers.join(", "))
+    };
+    let search_type = if args.query.is_some() {
+        "matching your query".to_string()
+    } else if let Some(reviewers) = &for_others {
+        format!("requesting review from {}", reviewers)
+    } else {
+        match mode {
+            SearchMode::Review => "requesting your review",
+            SearchMode::Own => "you have open",
+            SearchMode::All => "requesting your review or opened by you",
+            SearchMode::Inbox => "that need you",
+        }
+        .to_string()
+    };
+
+    let summary_what = if args.query.is_some() {
+        "matching your query".to_string()
+    } else if let Some(reviewers) = &for_others {
+        format!("awaiting review from {}", reviewers)
+    } else {
+        match mode {
+            SearchMode::Review => "awaiting review",
+            SearchMode::Own => "open",
+            SearchMode::All => "to review or open",
+            SearchMode::Inbox => "that need you",
+        }
+        .to_string()
+    };
+    let now = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or_default();
+    if args.notify_summary {
+        let summary = format::render_summary(&prs, &summary_what, now);
+        // A missing notifier shouldn't fail a scan that otherwise worked
+        if let Err(e) = notify("Review Radar", summary.trim_end()) {
+            eprintln!("⚠️  Could not show the summary notification: {}", e);
+        }
+    }
+
+    if args.record_history {
+        let entry = HistoryEntry {
+            at: now,
+            prs: prs
+                .iter()
+                .map(|pr| HistoryPr {
+                    url: pr.html_url.clone(),
+                    created_at: pr.created_at.clone(),
+                })
+                .collect(),
         };
-        println!("{}", search_desc);
-        let prs = client.search_own_prs(&orgs, username, repo_pattern)?;
-        (prs, "you have open")
+        History::append_to_path(&History::path()?, &entry)?;
+    }
+    if let Some(path) = &args.status_file {
+        write_atomically(path, &format!("{}\n", prs.len()))?;
+    }
+    if let Some(url) = args.discord_webhook.as_deref().filter(|_| !prs.is_empty()) {
+        let summary = format::render_summary(&prs, &summary_what, now);
+        post_webhook(url, &format::render_discord(&prs, &summary)?)
+            .map_err(|e| anyhow::anyhow!("Could not post to the Discord webhook: {}", e))?;
+    }
+
+    let use_color = match args.color {
+        ColorChoice::Always => true,
+        ColorChoice::Never => false,
+        ColorChoice::Auto => {
+            std::io::stdout().is_terminal() && std::env::var_os("NO_COLOR").is_none()
+        }
+    };
+
+    if let Some(base) = &args.url_base {
+        for pr in &mut prs {
+            pr.html_url = format::rebase_url(&pr.html_url, base);
+        }
+    }
+
+    let full_prs = prs.clone();
+
+    // Room left on a line for the "🔗 #1234 - " heading and a conflicts marker
+    const TITLE_MARGIN: usize = 24;
+    let title_width = match args.title_width {
+        Some(0) => None,
+        Some(width) => Some(width),
+        None if std::io::stdout().is_terminal() => {
+            terminal_size::terminal_size().map(|(terminal_size::Width(cols), _)| {
+                (cols as usize).saturating_sub(TITLE_MARGIN).max(20)
+            })
+        }
+        None => None,
+    };
+    if let (Some(width), OutputFormat::Text | OutputFormat::Kanban) = (title_width, output_format) {
+        for pr in &mut prs {
+            pr.title = format::truncate_title(&pr.title, width);
+        }
+    }
+
+    let sample_note = if args.sample.is_some() {
+        "🎲 Sampled repositories only, this is not a complete scan\n"
     } else {
-        let org_list = if orgs.len() > 2 {
-            format!("{} organizations", orgs.len())
-        } else {
-            orgs.join(", ")
+        ""
+    };
+    // Files get the full titles and URLs, without terminal colors or links
+    let render = |output_format: OutputFormat,
+                  prs: &[PullRequest],
+                  use_color: bool,
+                  links: bool|
+     -> Result<String> {
+        let output = match output_format {
+            OutputFormat::Json => {
+                let metadata = RunMetadata {
+                    version: env!("CARGO_PKG_VERSION").to_string(),
+                    generated_at: format_timestamp(now),
+                    mode: args.query.is_none().then_some(mode),
+                    query: args.query.clone(),
+                    targets: scanned_targets.clone(),
+                    repo_pattern: repo_pattern.map(str::to_string),
+                    filters: describe_filters(&options, &args),
+                };
+                format::render_json(prs, (!args.no_metadata && !args.json).then_some(&metadata))?
+            }
+            OutputFormat::GithubActions => format::render_github_actions(prs),
+            OutputFormat::Xml => format::render_xml(prs),
+            OutputFormat::Paths => format::render_paths(prs),
+            OutputFormat::Urls => format::render_urls(prs),
+            OutputFormat::Plain => format::render_plain(prs),
+            OutputFormat::Restructuredtext => format::render_restructuredtext(prs),
+            OutputFormat::Asciidoc => format::render_asciidoc(prs),
+            OutputFormat::HtmlFragment => format::render_html_fragment(prs),
+            OutputFormat::Toml => format::render_toml(prs)?,
+            OutputFormat::RawGh => format::render_raw_gh(prs)?,
+            OutputFormat::None => String::new(),
+            OutputFormat::Discord => {
+                format::render_discord(prs, &format::render_summary(prs, &summary_what, now))?
+            }
+            OutputFormat::MarkdownChecklist => format::render_markdown_checklist(prs),
+            OutputFormat::Swiftbar => format::render_swiftbar(prs),
+            OutputFormat::Obsidian => format::render_obsidian(prs, &format_date(now)),
+            OutputFormat::Mermaid => format::render_mermaid(prs, &format_date(now)),
+            OutputFormat::Opml => format::render_opml(prs),
+            OutputFormat::PandocJson => format::render_pandoc_json(prs)?,
+            OutputFormat::SummaryOnly => format::render_summary(prs, &summary_what, now),
+            OutputFormat::Delta => {
+                format::render_delta(prs, baseline.as_deref().unwrap_or_default())
+            }
+            OutputFormat::Text | OutputFormat::CountByRepo | OutputFormat::Kanban
+                if prs.is_empty() =>
+            {
+                if args.query.is_some() {
+                    "✅ No PRs found matching your query!\n".to_string()
+                } else if let Some(reviewers) = &for_others {
+                    format!("✅ No PRs found awaiting review from {}!\n", reviewers)
+                } else {
+                    match mode {
+                        SearchMode::Review => {
+                            "✅ No PRs found where your review has been requested!\n".to_string()
+                        }
+                        SearchMode::Own => "✅ No open PRs found by you!\n".to_string(),
+                        SearchMode::All => {
+                            "✅ No PRs found awaiting your review or opened by you!\n".to_string()
+                        }
+                        SearchMode::Inbox => "✅ Nothing needs you right now!\n".to_string(),
+                    }
+                }
+            }
+            OutputFormat::Text => format!(
+                "\n📋 Found {} PR(s) {}:\n{}\n{}{}",
+                prs.len(),
+                search_type,
+                sample_note,
+                format::render_oldest(prs, now, args.highlight_oldest),
+                format::render_text(prs, now, use_color.then_some(&config.age_colors), links)
+            ),
+            OutputFormat::Kanban => format!(
+                "\n📋 PR(s) {} by review state:\n{}\n{}",
+                search_type,
+                sample_note,
+                format::render_kanban(prs)
+            ),
+            OutputFormat::CountByRepo => format!(
+                "\n📋 PR(s) {} by repository:\n{}\n{}",
+                search_type,
+                sample_note,
+                format::render_count_by_repo(prs)
+            ),
         };
-        let search_desc = if let Some(pattern) = repo_pattern {
-            format!("🔍 Searching for PRs in {} where {} has been requested for review (repos matching '{}')...", org_list, username, pattern)
+
+        // Scripts consuming the output shouldn't have to strip padding meant for people
+        Ok(if args.quiet || output_format.is_machine_readable() {
+            trim_blank_lines(&output)
         } else {
-            format!(
-                "🔍 Searching for PRs in {} where {} has been requested for review...",
-                org_list, username
-            )
+            output
+        })
+    };
+    for (path, format) in &args.outputs {
+        write_atomically(path, &render(*format, &full_prs, false, false)?)
+            .map_err(|e| anyhow::anyhow!("Could not write {}: {}", path.display(), e))?;
+    }
+    let output = render(
+        output_format,
+        &prs,
+        use_color,
+        args.hyperlinks && std::io::stdout().is_terminal(),
+    )?;
+    display(&output, no_pager)?;
+    // Like grep: `if rr --format none; then ...` runs when there are PRs
+    if output_format == OutputFormat::None && prs.is_empty() {
+        std::process::exit(1);
+    }
+    Ok(())
+}
+
+/// Adds the repositories `found` couldn't check to `failed`, for `rr retry-failed`.
+fn record_failed(
+    failed: &mut Vec<FailedRepo>,
+    found: &SearchResult,
+    host: &Option<String>,
+    username: &str,
+) {
+    let unparsed = found.repos_unparsed.iter().map(|(repo, _)| repo);
+    for repo in found.repos_skipped.iter().chain(unparsed) {
+        let repo = FailedRepo {
+            host: host.clone(),
+            repo: repo.clone(),
+            username: username.to_string(),
+        };
+        if !failed.contains(&repo) {
+            failed.push(repo);
+        }
+    }
+}
+
+/// `output` without leading or trailing blank lines, still ending in a newline
+/// unless it's empty.
+fn trim_blank_lines(output: &str) -> String {
+    let trimmed = output.trim_start_matches('\n').trim_end_matches('\n');
+    if trimmed.is_empty() {
+        String::new()
+    } else {
+        format!("{}\n", trimmed)
+    }
+}
+
+/// Prints `question` and reads one answer line from stdin, trimmed.
+fn prompt(question: &str) -> Result<String> {
+    print!("{}", question);
+    std::io::stdout().flush()?;
+    let mut answer = String::new();
+    if std::io::stdin().read_line(&mut answer)? == 0 {
+        return Err(anyhow::anyhow!("No answer given (end of input)"));
+    }
+    Ok(answer.trim().to_string())
+}
+
+/// `rr init --interactive`: asks for the orgs (checked against the ones the
+/// user belongs to), the login (defaulting to the authenticated one) and an
+/// optional pattern, previews how many repositories match and saves after
+/// confirmation.
+fn init_interactive(token: Option<String>) -> Result<()> {
+    let client = GitHubClient::new(None, ScanOptions::default()).with_token(token);
+    // Without read:org the list is unknown, which only skips the membership check
+    let member_orgs = client.my_orgs().unwrap_or_else(|e| {
+        eprintln!("⚠️  {}", e);
+        Vec::new()
+    });
+    if !member_orgs.is_empty() {
+        println!("Organizations you belong to:");
+        for (i, org) in member_orgs.iter().enumerate() {
+            println!("  {:>2}. {}", i + 1, org);
+        }
+    }
+
+    let orgs = loop {
+        let answer =
+            prompt("Organizations to scan (comma-separated names, or numbers like 1,3-5): ")?;
+        if answer.is_empty() {
+            println!("❌ At least one organization is needed");
+            continue;
+        }
+        // Numbers pick from the list above; anything else is taken as org names
+        let orgs: Vec<String> = match parse_selection(&answer, member_orgs.len()) {
+            Ok(indices) => indices
+                .into_iter()
+                .map(|i| member_orgs[i].clone())
+                .collect(),
+            Err(_) => dedup_orgs(answer.split(',').map(|s| s.trim().to_string()).collect()).0,
         };
-        println!("{}", search_desc);
-        let prs = client.search_prs_for_user(&orgs, username, repo_pattern)?;
-        (prs, "requesting your review")
+        let unknown: Vec<&String> = orgs
+            .iter()
+            .filter(|org| !member_orgs.is_empty() && !member_orgs.contains(org))
+            .collect();
+        if !unknown.is_empty() {
+            let names: Vec<&str> = unknown.iter().map(|org| org.as_str()).collect();
+            println!("⚠️  You're not a member of: {}", names.join(", "));
+            let keep = prompt(
+                "Keep them anyway (their public repositories can still be scanned)? [y/N] ",
+            )?;
+            if !matches!(keep.to_lowercase().as_str(), "y" | "yes") {
+                continue;
+            }
+        }
+        break orgs;
+    };
+
+    let login = client.resolve_username("@me", false).ok();
+    let username = loop {
+        let answer = match &login {
+            Some(login) => prompt(&format!("Your GitHub username [{}]: ", login))?,
+            None => prompt("Your GitHub username: ")?,
+        };
+        match (answer.is_empty(), &login) {
+            (false, _) => break answer,
+            (true, Some(login)) => break login.clone(),
+            (true, None) => println!("❌ A username is needed"),
+        }
+    };
+
+    let repo_pattern = loop {
+        let answer = prompt("Repository pattern (regex, Enter for all repositories): ")?;
+        if answer.is_empty() {
+            break None;
+        }
+        match compile_repo_pattern(&answer) {
+            Ok(_) => break Some(answer),
+            Err(e) => println!("❌ {}", e),
+        }
     };
 
-    if prs.is_empty() {
-        if args.own_prs {
-            println!("✅ No open PRs found by you!");
+    let regex = repo_pattern
+        .as_deref()
+        .map(compile_repo_pattern)
+        .transpose()?;
+    for org in &orgs {
+        match client.list_org_repos(org)? {
+            Some(repos) => {
+                let matching = repos
+                    .iter()
+                    .filter(|repo| regex.as_ref().is_none_or(|re| re.is_match(&repo.name)))
+                    .count();
+                println!(
+                    "🏛️  {}: {} of {} repositories would be scanned",
+                    org,
+                    matching,
+                    repos.len()
+                );
+            }
+            None => println!("⚠️  {}: couldn't list its repositories", org),
+        }
+    }
+
+    let save = prompt("Save this configuration? [Y/n] ")?;
+    if matches!(save.to_lowercase().as_str(), "n" | "no") {
+        println!("ℹ️  No changes saved");
+        return Ok(());
+    }
+    let config = Config {
+        orgs,
+        username,
+        repo_pattern,
+        ..Default::default()
+    };
+    config.save()?;
+    println!("✅ Configuration saved successfully!");
+    println!("📋 Organizations: {}", config.orgs.join(", "));
+    if let Some(pattern) = &config.repo_pattern {
+        println!("📋 Repository filter pattern: {}", pattern);
+    }
+    Ok(())
+}
+
+/// `rr orgs sync`: lets the user pick from the orgs they belong to, previews
+/// how the configured orgs would change and saves them after confirmation.
+/// Configured orgs the user isn't a member of are left alone.
+fn sync_orgs(token: Option<String>, all: bool, yes: bool) -> Result<()> {
+    let mut config = Config::load()?;
+    let interactive = std::io::stdin().is_terminal();
+    let member_orgs = GitHubClient::new(None, ScanOptions::default())
+        .with_token(token)
+        .my_orgs()?;
+    if member_orgs.is_empty() {
+        println!("ℹ️  You don't belong to any organizations");
+        return Ok(());
+    }
+
+    let selected: Vec<&String> = if all {
+        member_orgs.iter().collect()
+    } else if !interactive {
+        return Err(anyhow::anyhow!(
+            "Can't ask which organizations to pick without a terminal: pass --all"
+        ));
+    } else {
+        println!("Organizations you belong to:");
+        for (i, org) in member_orgs.iter().enumerate() {
+            let mark = if config.orgs.contains(org) {
+                " (configured)"
+            } else {
+                ""
+            };
+            println!("  {:>2}. {}{}", i + 1, org, mark);
+        }
+        print!("Organizations to scan (e.g. 1,3-5, Enter for all): ");
+        std::io::stdout().flush()?;
+        let mut answer = String::new();
+        std::io::stdin().read_line(&mut answer)?;
+        if answer.trim().is_empty() {
+            member_orgs.iter().collect()
         } else {
-            println!("✅ No PRs found where your review has been requested!");
+            parse_selection(&answer, member_orgs.len())?
+                .into_iter()
+                .map(|i| &member_orgs[i])
+                .collect()
         }
+    };
+
+    let removed: Vec<&String> = member_orgs
+        .iter()
+        .filter(|org| config.orgs.contains(org) && !selected.contains(org))
+        .collect();
+    let added: Vec<&String> = selected
+        .iter()
+        .copied()
+        .filter(|org| !config.orgs.contains(org))
+        .collect();
+    if added.is_empty() && removed.is_empty() {
+        println!("ℹ️  Organizations are already up to date");
         return Ok(());
     }
 
-    println!("\n📋 Found {} PR(s) {}:\n", prs.len(), search_type);
+    println!("Changes to the configured organizations:");
+    for org in &added {
+        println!("  + {}", org);
+    }
+    for org in &removed {
+        println!("  - {}", org);
+    }
+    if !yes {
+        if !interactive {
+            return Err(anyhow::anyhow!(
+                "Can't confirm the changes without a terminal: pass --yes"
+            ));
+        }
+        print!("Save these changes? [y/N] ");
+        std::io::stdout().flush()?;
+        let mut answer = String::new();
+        std::io::stdin().read_line(&mut answer)?;
+        if !matches!(answer.trim().to_lowercase().as_str(), "y" | "yes") {
+            println!("ℹ️  No changes saved");
+            return Ok(());
+        }
+    }
+
+    for org in removed {
+        config.remove_org(org);
+    }
+    for org in added {
+        config.add_org(org.clone());
+    }
+    config.save()?;
+    println!("✅ Configuration updated successfully!");
+    Ok(())
+}
+
+/// The result-changing flags in effect, written the way they're passed.
+fn describe_filters(options: &ScanOptions, args: &Args) -> Vec<String> {
+    let mut filters: Vec<String> = args
+        .skip_orgs
+        .iter()
+        .map(|org| format!("--skip-org {}", org))
+        .collect();
+    if let Some(visibility) = options.visibility {
+        filters.push(format!(
+            "--visibility {}",
+            visibility.to_possible_value().unwrap().get_name()
+        ));
+    }
+    if let Some(min) = options.min_permission {
+        filters.push(format!(
+            "--min-permission {}",
+            min.to_possible_value().unwrap().get_name()
+        ));
+    }
+    if options.conflicts_only {
+        filters.push("--conflicts-only".to_string());
+    }
+    if options.team_only {
+        filters.push("--team-only".to_string());
+    }
+    if options.include_closed {
+        filters.push("--include-closed-requests".to_string());
+    }
+    if options.strict {
+        filters.push("--strict".to_string());
+    }
+    if let Some(since) = options.active_since {
+        filters.push(format!("--repos-active-since {}", format_date(since)));
+    }
+    if let Some((count, seed)) = options.sample {
+        filters.push(format!("--sample {} --seed {}", count, seed));
+    }
+    if let Some(limit) = options.pr_limit {
+        filters.push(format!("--pr-limit {}", limit));
+    }
+    for arg in &options.gh_args {
+        filters.push(format!("--gh-arg {}", arg));
+    }
+    if let Some(pattern) = &args.title_pattern {
+        filters.push(format!("--title-pattern {}", pattern));
+    }
+    if let Some(pattern) = &args.exclude_title_pattern {
+        filters.push(format!("--exclude-title-pattern {}", pattern));
+    }
+    if let Some(project) = &args.project {
+        filters.push(format!("--project {}", project));
+    }
+    filters
+}
+
+/// Routes a search's progress like `status!` and asks questions on the terminal.
+struct StatusReporter;
+
+impl Reporter for StatusReporter {
+    fn progress(&self, message: &str) {
+        status!("{}", message);
+    }
+
+    fn warn(&self, message: &str) {
+        eprintln!("{}", message);
+    }
+
+    /// Without a terminal to ask on, large scans are declined.
+    fn confirm_broad_scan(&self, count: usize) -> Result<bool> {
+        if !std::io::stdin().is_terminal() {
+            return Ok(false);
+        }
+
+        eprint!(
+            "⚠️  About to scan {} repositories, which may exhaust your API rate limit. Continue? [y/N] ",
+            count
+        );
+        std::io::stderr().flush()?;
+        let mut answer = String::new();
+        std::io::stdin().read_line(&mut answer)?;
+        Ok(matches!(answer.trim().to_lowercase().as_str(), "y" | "yes"))
+    }
+}
+
+/// Runs `gh auth status`, retrying once after a short pause when the failure
+/// looks like a network hiccup. Fails outright if GitHub stays unreachable,
+/// since that says nothing about whether the user is logged in.
+fn check_auth(token: Option<&str>) -> Result<bool> {
+    let mut retried = false;
+    loop {
+        let output = gh_command(None, token).args(["auth", "status"]).output()?;
+        if output.status.success() {
+            return Ok(true);
+        }
+
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        if !is_network_error(&stderr) {
+            return Ok(false);
+        }
+        if retried {
+            return Err(anyhow::anyhow!(
+                "Could not reach GitHub to check authentication: {}",
+                stderr.trim()
+            ));
+        }
+        eprintln!("⚠️  Could not reach GitHub, retrying...");
+        std::thread::sleep(AUTH_RETRY_DELAY);
+        retried = true;
+    }
+}
+
+/// Shows a desktop notification through the platform's notifier:
+/// `osascript` on macOS, `notify-send` elsewhere.
+fn notify(title: &str, message: &str) -> Result<()> {
+    let status = if cfg!(target_os = "macos") {
+        let quote = |s: &str| format!("\"{}\"", s.replace('\\', "\\\\").replace('"', "\\\""));
+        Command::new("osascript")
+            .arg("-e")
+            .arg(format!(
+                "display notification {} with title {}",
+                quote(message),
+                quote(title)
+            ))
+            .status()
+    } else {
+        Command::new("notify-send").args([title, message]).status()
+    }?;
+    if !status.success() {
+        return Err(anyhow::anyhow!("the notifier exited with {}", status));
+    }
+    Ok(())
+}
+
+/// POSTs a JSON `payload` to a webhook `url` with `curl`.
+fn post_webhook(url: &str, payload: &str) -> Result<()> {
+    let mut child = Command::new("curl")
+        .args(["--silent", "--show-error", "--fail"])
+        .args(["--header", "Content-Type: application/json"])
+        .args(["--data-binary", "@-", url])
+        .stdin(Stdio::piped())
+        .stdout(Stdio::null())
+        .spawn()
+        .map_err(|e| anyhow::anyhow!("couldn't run curl: {}", e))?;
+    if let Some(mut stdin) = child.stdin.take() {
+        stdin.write_all(payload.as_bytes())?;
+    }
+    let status = child.wait()?;
+    if !status.success() {
+        return Err(anyhow::anyhow!("curl exited with {}", status));
+    }
+    Ok(())
+}
+
+/// `--orgs` with each name that isn't an existing org or user expanded to
+/// the orgs you belong to that it is the start of. Memberships are only
+/// fetched when some name doesn't exist as typed.
+fn expand_orgs(
+    orgs: Vec<String>,
+    token: Option<&str>,
+    page_size: Option<u16>,
+) -> Result<Vec<String>> {
+    let options = ScanOptions {
+        page_size,
+        ..Default::default()
+    };
+    let client = GitHubClient::new(None, options).with_token(token.map(str::to_string));
+    let mut memberships = None;
+    let mut expanded = Vec::new();
+    for org in orgs {
+        if client.owner_exists(&org)? {
+            expanded.push(org);
+            continue;
+        }
+        if memberships.is_none() {
+            memberships = Some(client.my_orgs()?);
+        }
+        let matches = expand_org_prefix(&org, memberships.as_deref().unwrap_or_default())?;
+        statusln!("🔎 Expanded '{}' to {}", org, matches.join(", "));
+        expanded.extend(matches);
+    }
+    Ok(expanded)
+}
 
-    for pr in prs {
-        println!("🔗 #{} - {}", pr.number, pr.title);
-        println!("   👤 Author: {}", pr.user.login);
-        println!("   🌐 URL: {}", pr.html_url);
-        println!();
+/// Prints the final output, through `$PAGER` (like git) when stdout is a
+/// terminal that it would not fit on.
+fn display(output: &str, no_pager: bool) -> Result<()> {
+    let fits = match terminal_size::terminal_size() {
+        Some((_, terminal_size::Height(rows))) => output.lines().count() < rows as usize,
+        None => true,
+    };
+    if no_pager || fits || !std::io::stdout().is_terminal() {
+        print!("{}", output);
+        return Ok(());
     }
 
+    let pager = std::env::var("PAGER").unwrap_or_else(|_| "less -R".to_string());
+    let mut parts = pager.split_whitespace();
+    let Some(program) = parts.next() else {
+        print!("{}", output);
+        return Ok(());
+    };
+
+    match Command::new(program)
+        .args(parts)
+        .stdin(Stdio::piped())
+        .spawn()
+    {
+        Ok(mut child) => {
+            if let Some(mut stdin) = child.stdin.take() {
+                // The pager may quit before reading everything; that's fine
+                let _ = stdin.write_all(output.as_bytes());
+            }
+            child.wait()?;
+        }
+        // Fall back to plain output if the pager can't be started
+        Err(_) => print!("{}", output),
+    }
     Ok(())
 }
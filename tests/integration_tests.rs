@@ -4,6 +4,21 @@ use review_radar::Config;
 use std::fs;
 use tempfile::TempDir;
 
+/// Points the config, state and cache directories of `rr` at `dir`, so a test
+/// never reads or leaves files outside its temporary directory.
+trait XdgDirs {
+    fn xdg_dirs(&mut self, dir: impl AsRef<std::ffi::OsStr>) -> &mut Self;
+}
+
+impl XdgDirs for Command {
+    fn xdg_dirs(&mut self, dir: impl AsRef<std::ffi::OsStr>) -> &mut Self {
+        let dir = dir.as_ref();
+        self.env("XDG_CONFIG_HOME", dir)
+            .env("XDG_STATE_HOME", dir)
+            .env("XDG_CACHE_HOME", dir)
+    }
+}
+
 fn create_test_config(
     temp_dir: &TempDir,
     orgs: Vec<&str>,
@@ -18,6 +33,7 @@ fn create_test_config(
         orgs: orgs.iter().map(|s| s.to_string()).collect(),
         username: username.to_string(),
         repo_pattern: repo_pattern.map(|s| s.to_string()),
+        ..Default::default()
     };
 
     let config_path = review_radar_dir.join("config.toml");
@@ -26,6 +42,105 @@ fn create_test_config(
     config_dir.to_string_lossy().to_string()
 }
 
+/// Installs a fake `gh` into `<temp>/bin` that answers from fixture files:
+/// `repo list <org>` prints `repos/<org>.json` and `pr list --repo <org>/<repo>`
+/// prints `prs/<org>_<repo>.json`, `search prs` prints `search.json`, or
+/// without it answers `user-review-requested:`, `reviewed-by:`, `org:` and
+/// `is:open` queries from the `prs/<org>_*.json` lists, `api <endpoint>`
+/// prints `api/<endpoint>` (slashes replaced by underscores) and
+/// `project item-list <number>` prints
+/// `projects/<number>.json`. `pr list` and `project item-list` apply `--jq`
+/// like gh does, through `jq`. Every call is appended to `calls.log`,
+/// prefixed with `[$GH_HOST]` and `<$GH_TOKEN>` when set.
+/// Returns a `PATH` value with the fake `gh` first.
+fn install_fake_gh(temp_dir: &TempDir) -> String {
+    use std::os::unix::fs::PermissionsExt;
+
+    let bin_dir = temp_dir.path().join("bin");
+    fs::create_dir_all(bin_dir.join("repos")).unwrap();
+    fs::create_dir_all(bin_dir.join("prs")).unwrap();
+    fs::create_dir_all(bin_dir.join("api")).unwrap();
+    fs::create_dir_all(bin_dir.join("projects")).unwrap();
+
+    let script = r#"#!/bin/sh
+dir="$(dirname "$0")"
+filter=""
+echo "${GH_HOST:+[$GH_HOST] }${GH_TOKEN:+<$GH_TOKEN> }$*" >> "$dir/calls.log"
+case "$1 $2" in
+  "auth status") [ -f "$dir/auth_error" ] || exit 0; cat "$dir/auth_error" >&2; exit 1 ;;
+  "repo list") f="$dir/repos/$3.json" ;;
+  "pr list") f="$dir/prs/$(echo "$4" | tr / _).json"
+    prev=""; for arg in "$@"; do [ "$prev" = "--jq" ] && filter="$arg"; prev="$arg"; done ;;
+  "project item-list") f="$dir/projects/$3.json"
+    prev=""; for arg in "$@"; do [ "$prev" = "--jq" ] && filter="$arg"; prev="$arg"; done ;;
+  "search prs") f="$dir/search.json"
+    if [ ! -f "$f" ]; then
+      login=""; reviewer=""; orgs=""; open=""; limit=1000; prev=""
+      for arg in "$@"; do
+        case "$arg" in
+          user-review-requested:*) login="${arg#*:}" ;;
+          reviewed-by:*) reviewer="${arg#*:}" ;;
+          org:*) orgs="$orgs ${arg#org:}" ;;
+          is:open) open=1 ;;
+        esac
+        [ "$prev" = "--limit" ] && limit="$arg"
+        prev="$arg"
+      done
+      for org in $orgs; do
+        for prs in "$dir/prs/${org}_"*.json; do
+          [ -f "$prs" ] || continue
+          repo="${prs##*/}"; repo="${repo#"${org}_"}"; repo="${repo%.json}"
+          jq -c --arg login "$login" --arg reviewer "$reviewer" --arg org "$org" \
+            --arg repo "$repo" --arg open "$open" \
+            '[.[] | select(any(.reviewRequests[]?; .login == $login)
+                           or any(.reviews[]?; .author.login == $reviewer))
+                  | select($open == "" or (.state // "OPEN") == "OPEN")
+                  | . + {repository: {name: $repo, nameWithOwner: "\($org)/\($repo)"}}]' "$prs"
+        done
+      done | jq -c -s --argjson limit "$limit" 'add // [] | .[:$limit]'
+      exit 0
+    fi ;;
+  "api "*) f="$dir/api/$(echo "$2" | tr / _)" ;;
+  *) exit 1 ;;
+esac
+[ -f "$f" ] || exit 1
+if [ -n "$filter" ]; then jq -c -r "$filter" "$f"; else cat "$f"; fi
+"#;
+    let gh_path = bin_dir.join("gh");
+    fs::write(&gh_path, script).unwrap();
+    fs::set_permissions(&gh_path, fs::Permissions::from_mode(0o755)).unwrap();
+
+    format!(
+        "{}:{}",
+        bin_dir.display(),
+        std::env::var("PATH").unwrap_or_default()
+    )
+}
+
+/// Makes `rr` list each repository's PRs, as `--legacy-scan` does, for
+/// tests of behavior only that scan has.
+fn use_legacy_scan(temp_dir: &TempDir) {
+    let config_path = temp_dir.path().join("config/review-radar/config.toml");
+    let mut config = Config::load_from_path(&config_path).unwrap();
+    config.defaults.legacy_scan = true;
+    config.save_to_path(&config_path).unwrap();
+}
+
+fn fake_gh_fixture(temp_dir: &TempDir, name: &str, json: &str) {
+    fs::write(temp_dir.path().join("bin").join(name), json).unwrap();
+}
+
+fn fake_gh_calls(temp_dir: &TempDir) -> String {
+    fs::read_to_string(temp_dir.path().join("bin").join("calls.log")).unwrap_or_default()
+}
+
+fn review_requested_pr(number: u32, title: &str, reviewer: &str) -> String {
+    format!(
+        r#"{{"number":{},"title":"{}","url":"https://github.com/pull/{}","author":{{"login":"alice"}},"reviewRequests":[{{"login":"{}"}}]}}"#,
+        number, title, number, reviewer
+    )
+}
+
 #[test]
 fn test_help_command() {
     let mut cmd = Command::cargo_bin("rr").unwrap();
@@ -37,6 +152,21 @@ fn test_help_command() {
         ));
 }
 
+#[test]
+fn test_version_command() {
+    let mut cmd = Command::cargo_bin("rr").unwrap();
+    cmd.arg("version")
+        .assert()
+        .success()
+        .stdout(predicate::str::contains(format!(
+            "rr {}",
+            env!("CARGO_PKG_VERSION")
+        )))
+        .stdout(predicate::str::contains("Git commit:"))
+        .stdout(predicate::str::contains("Build date:"))
+        .stdout(predicate::str::contains("GitHub CLI:"));
+}
+
 #[test]
 fn test_config_command_without_config() {
     let temp_dir = TempDir::new().unwrap();
@@ -44,7 +174,7 @@ fn test_config_command_without_config() {
 
     let mut cmd = Command::cargo_bin("rr").unwrap();
     cmd.arg("config")
-        .env("XDG_CONFIG_HOME", config_dir)
+        .xdg_dirs(&config_dir)
         .assert()
         .success()
         .stdout(predicate::str::contains("Configuration not found"));
@@ -59,7 +189,7 @@ fn test_init_command() {
     cmd.arg("init")
         .arg("test-org1,test-org2")
         .arg("testuser")
-        .env("XDG_CONFIG_HOME", config_dir.clone())
+        .xdg_dirs(&config_dir)
         .assert()
         .success()
         .stdout(predicate::str::contains("Configuration saved successfully"))
@@ -88,7 +218,7 @@ fn test_init_command_with_pattern() {
         .arg("testuser")
         .arg("-r")
         .arg("backend-.*")
-        .env("XDG_CONFIG_HOME", config_dir.clone())
+        .xdg_dirs(&config_dir)
         .assert()
         .success()
         .stdout(predicate::str::contains(
@@ -109,7 +239,7 @@ fn test_set_command_add_org() {
     cmd.arg("set")
         .arg("--orgs")
         .arg("+org2")
-        .env("XDG_CONFIG_HOME", &config_dir)
+        .xdg_dirs(&config_dir)
         .assert()
         .success()
         .stdout(predicate::str::contains("Added organization: org2"));
@@ -124,7 +254,7 @@ fn test_set_command_remove_org() {
     cmd.arg("set")
         .arg("--orgs")
         .arg("-org1")
-        .env("XDG_CONFIG_HOME", &config_dir)
+        .xdg_dirs(&config_dir)
         .assert()
         .success()
         .stdout(predicate::str::contains("Removed organization: org1"));
@@ -139,12 +269,92 @@ fn test_set_command_replace_orgs() {
     cmd.arg("set")
         .arg("--orgs")
         .arg("new-org1,new-org2")
-        .env("XDG_CONFIG_HOME", &config_dir)
+        .xdg_dirs(&config_dir)
         .assert()
         .success()
         .stdout(predicate::str::contains("Updated organizations"));
 }
 
+#[test]
+fn test_set_command_check() {
+    let temp_dir = TempDir::new().unwrap();
+    let config_dir = create_test_config(&temp_dir, vec!["org1"], "testuser", None);
+    let path = install_fake_gh(&temp_dir);
+    fake_gh_fixture(&temp_dir, "api/user", "realuser\n");
+    let config_path = temp_dir.path().join("config/review-radar/config.toml");
+
+    let mut cmd = Command::cargo_bin("rr").unwrap();
+    cmd.args(["set", "--username", "typo-user", "--check"])
+        .xdg_dirs(&config_dir)
+        .env("PATH", &path)
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains(
+            "gh is logged in to github.com as 'realuser', not 'typo-user'",
+        ));
+    assert_eq!(
+        Config::load_from_path(&config_path).unwrap().username,
+        "testuser"
+    );
+
+    let mut cmd = Command::cargo_bin("rr").unwrap();
+    cmd.args(["set", "--username", "RealUser", "--check"])
+        .xdg_dirs(&config_dir)
+        .env("PATH", &path)
+        .assert()
+        .success()
+        .stdout(predicate::str::contains(
+            "Logged in to github.com as realuser",
+        ));
+    assert_eq!(
+        Config::load_from_path(&config_path).unwrap().username,
+        "realuser"
+    );
+
+    // The account checked is the one the token file logs in as
+    let token_path = temp_dir.path().join("token");
+    fs::write(&token_path, "ghp_fromfile\n").unwrap();
+    let mut cmd = Command::cargo_bin("rr").unwrap();
+    cmd.args(["set", "--username", "realuser", "--check"])
+        .xdg_dirs(&config_dir)
+        .env("PATH", &path)
+        .env("REVIEW_RADAR_TOKEN_FILE", &token_path)
+        .assert()
+        .success();
+    assert!(fake_gh_calls(&temp_dir).contains("<ghp_fromfile> api user"));
+
+    let mut cmd = Command::cargo_bin("rr").unwrap();
+    cmd.args([
+        "set",
+        "--host",
+        "github.example.com",
+        "--orgs",
+        "corp",
+        "--check",
+    ])
+    .xdg_dirs(&config_dir)
+    .env("PATH", &path)
+    .assert()
+    .success()
+    .stdout(predicate::str::contains(
+        "Logged in to github.example.com as realuser",
+    ));
+    assert!(fake_gh_calls(&temp_dir).contains("api user --hostname github.example.com"));
+    let config = Config::load_from_path(&config_path).unwrap();
+    assert_eq!(config.hosts[0].orgs, vec!["corp".to_string()]);
+    assert_eq!(config.hosts[0].username, "@me");
+    assert_eq!(config.orgs, vec!["org1".to_string()]);
+
+    // Without --check, set never talks to GitHub
+    fs::remove_file(temp_dir.path().join("bin/api/user")).unwrap();
+    let mut cmd = Command::cargo_bin("rr").unwrap();
+    cmd.args(["set", "--username", "offline-user"])
+        .xdg_dirs(&config_dir)
+        .env("PATH", &path)
+        .assert()
+        .success();
+}
+
 #[test]
 fn test_set_command_invalid_regex() {
     let temp_dir = TempDir::new().unwrap();
@@ -154,7 +364,7 @@ fn test_set_command_invalid_regex() {
     cmd.arg("set")
         .arg("-r")
         .arg("[invalid")
-        .env("XDG_CONFIG_HOME", &config_dir)
+        .xdg_dirs(&config_dir)
         .assert()
         .success()
         .stdout(predicate::str::contains("Invalid regex pattern"));
@@ -169,7 +379,7 @@ fn test_set_command_clear_pattern() {
     cmd.arg("set")
         .arg("-r")
         .arg("none")
-        .env("XDG_CONFIG_HOME", &config_dir)
+        .xdg_dirs(&config_dir)
         .assert()
         .success()
         .stdout(predicate::str::contains(
@@ -189,7 +399,7 @@ fn test_config_command_with_config() {
 
     let mut cmd = Command::cargo_bin("rr").unwrap();
     cmd.arg("config")
-        .env("XDG_CONFIG_HOME", &config_dir)
+        .xdg_dirs(&config_dir)
         .assert()
         .success()
         .stdout(predicate::str::contains("Organizations: org1, org2"))
@@ -197,13 +407,30 @@ fn test_config_command_with_config() {
         .stdout(predicate::str::contains("Repository filter: backend-.*"));
 }
 
+#[test]
+fn test_config_command_no_auth_check() {
+    let temp_dir = TempDir::new().unwrap();
+    let config_dir = create_test_config(&temp_dir, vec!["org1"], "testuser", None);
+    let path = install_fake_gh(&temp_dir);
+
+    let mut cmd = Command::cargo_bin("rr").unwrap();
+    cmd.args(["config", "--no-auth-check"])
+        .xdg_dirs(&config_dir)
+        .env("PATH", &path)
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("Username: testuser"))
+        .stdout(predicate::str::contains("GitHub CLI").not());
+    assert!(!fake_gh_calls(&temp_dir).contains("auth status"));
+}
+
 #[test]
 fn test_main_command_no_config() {
     let temp_dir = TempDir::new().unwrap();
     let config_dir = temp_dir.path().join("empty");
 
     let mut cmd = Command::cargo_bin("rr").unwrap();
-    cmd.env("XDG_CONFIG_HOME", config_dir)
+    cmd.xdg_dirs(&config_dir)
         .assert()
         .failure()
         .stderr(predicate::str::contains("Configuration not found"));
@@ -215,12 +442,26 @@ fn test_main_command_no_orgs_configured() {
     let config_dir = create_test_config(&temp_dir, vec![], "testuser", None);
 
     let mut cmd = Command::cargo_bin("rr").unwrap();
-    cmd.env("XDG_CONFIG_HOME", &config_dir)
+    cmd.xdg_dirs(&config_dir)
         .assert()
         .failure()
         .stderr(predicate::str::contains("No organizations configured"));
 }
 
+#[test]
+fn test_repo_pattern_without_orgs() {
+    let temp_dir = TempDir::new().unwrap();
+    let config_dir = create_test_config(&temp_dir, vec![], "testuser", Some("api-.*"));
+
+    let mut cmd = Command::cargo_bin("rr").unwrap();
+    cmd.xdg_dirs(&config_dir)
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains(
+            "The repository pattern 'api-.*' needs at least one organization",
+        ));
+}
+
 #[test]
 fn test_command_line_org_override() {
     let temp_dir = TempDir::new().unwrap();
@@ -230,7 +471,7 @@ fn test_command_line_org_override() {
     let mut cmd = Command::cargo_bin("rr").unwrap();
     cmd.arg("--orgs")
         .arg("override-org")
-        .env("XDG_CONFIG_HOME", &config_dir)
+        .xdg_dirs(&config_dir)
         .assert()
         .failure(); // Expected to fail due to gh CLI requirements
 }
@@ -242,10 +483,7 @@ fn test_own_flag() {
 
     // This test will fail because we don't have gh CLI access, but it tests argument parsing
     let mut cmd = Command::cargo_bin("rr").unwrap();
-    cmd.arg("--own")
-        .env("XDG_CONFIG_HOME", &config_dir)
-        .assert()
-        .failure(); // Expected to fail due to gh CLI requirements
+    cmd.arg("--own").xdg_dirs(&config_dir).assert().failure(); // Expected to fail due to gh CLI requirements
 }
 
 #[test]
@@ -257,7 +495,2985 @@ fn test_repo_pattern_override() {
     let mut cmd = Command::cargo_bin("rr").unwrap();
     cmd.arg("-r")
         .arg("test-.*")
-        .env("XDG_CONFIG_HOME", &config_dir)
+        .xdg_dirs(&config_dir)
         .assert()
         .failure(); // Expected to fail due to gh CLI requirements
 }
+
+#[test]
+fn test_repo_patterns_list() {
+    let temp_dir = TempDir::new().unwrap();
+    let config_dir = create_test_config(&temp_dir, vec!["org1"], "testuser", None);
+    let path = install_fake_gh(&temp_dir);
+    fake_gh_fixture(
+        &temp_dir,
+        "repos/org1.json",
+        r#"[{"name":"backend-jobs"},{"name":"api-gateway"},{"name":"web"}]"#,
+    );
+    for (number, repo) in ["backend-jobs", "api-gateway", "web"].iter().enumerate() {
+        fake_gh_fixture(
+            &temp_dir,
+            &format!("prs/org1_{}.json", repo),
+            &format!(
+                "[{}]",
+                review_requested_pr(number as u32 + 1, "Change", "testuser")
+            ),
+        );
+    }
+
+    let mut cmd = Command::cargo_bin("rr").unwrap();
+    cmd.args(["set", "-r", "^backend-", "-r", "^api-"])
+        .xdg_dirs(&config_dir)
+        .assert()
+        .success()
+        .stdout(predicate::str::contains(
+            "Updated repository filter pattern",
+        ));
+    let config_path = std::path::Path::new(&config_dir)
+        .join("review-radar")
+        .join("config.toml");
+    let config = Config::load_from_path(&config_path).unwrap();
+    assert_eq!(config.repo_patterns, vec!["^backend-", "^api-"]);
+
+    // The search covers the org, and the patterns pick from what it found
+    let mut cmd = Command::cargo_bin("rr").unwrap();
+    cmd.args(["--format", "paths"])
+        .xdg_dirs(&config_dir)
+        .env("PATH", &path)
+        .assert()
+        .success()
+        .stdout("org1/api-gateway\norg1/backend-jobs\n");
+
+    // as does the repository scan
+    let mut cmd = Command::cargo_bin("rr").unwrap();
+    cmd.arg("--legacy-scan")
+        .xdg_dirs(&config_dir)
+        .env("PATH", &path)
+        .assert()
+        .success();
+    let calls = fake_gh_calls(&temp_dir);
+    assert!(calls.contains("--repo org1/backend-jobs"), "{}", calls);
+    assert!(calls.contains("--repo org1/api-gateway"), "{}", calls);
+    assert!(!calls.contains("--repo org1/web"), "{}", calls);
+}
+
+#[test]
+fn test_failed_scan_keeps_checkpoint() {
+    let temp_dir = TempDir::new().unwrap();
+    let config_dir = create_test_config(&temp_dir, vec!["org1"], "testuser", None);
+    use_legacy_scan(&temp_dir);
+    let path = install_fake_gh(&temp_dir);
+    fake_gh_fixture(
+        &temp_dir,
+        "repos/org1.json",
+        r#"[{"name":"api"},{"name":"locked"}]"#,
+    );
+    fake_gh_fixture(
+        &temp_dir,
+        "prs/org1_api.json",
+        &format!("[{}]", review_requested_pr(1, "Api change", "testuser")),
+    );
+
+    Command::cargo_bin("rr")
+        .unwrap()
+        .args(["--strict", "--concurrency", "1"])
+        .xdg_dirs(&config_dir)
+        .env("PATH", &path)
+        .assert()
+        .failure();
+
+    // Saved on the failure, though well within the interval since the last write
+    let checkpoint_path = std::path::Path::new(&config_dir)
+        .join("review-radar")
+        .join("checkpoint.json");
+    let checkpoint = review_radar::Checkpoint::load_from_path(&checkpoint_path)
+        .unwrap()
+        .unwrap();
+    assert_eq!(checkpoint.completed, ["org1/api"]);
+    assert_eq!(checkpoint.prs[0].title, "Api change");
+}
+
+#[test]
+fn test_resume_skips_checked_repos() {
+    let temp_dir = TempDir::new().unwrap();
+    let config_dir = create_test_config(&temp_dir, vec!["org1"], "testuser", None);
+    let path = install_fake_gh(&temp_dir);
+    fake_gh_fixture(
+        &temp_dir,
+        "repos/org1.json",
+        r#"[{"name":"api"},{"name":"web"}]"#,
+    );
+    fake_gh_fixture(
+        &temp_dir,
+        "prs/org1_web.json",
+        &format!("[{}]", review_requested_pr(2, "Web change", "testuser")),
+    );
+
+    // Simulate a previous run that was interrupted after checking org1/api
+    let checkpoint_path = std::path::Path::new(&config_dir)
+        .join("review-radar")
+        .join("checkpoint.json");
+    let mut checkpoint =
+        review_radar::Checkpoint::new("review|testuser|org1||fields:created-at".to_string());
+    checkpoint.completed.push("org1/api".to_string());
+    checkpoint.prs.push(review_radar::PullRequest {
+        number: 1,
+        title: "Api change".to_string(),
+        html_url: "https://github.com/org1/api/pull/1".to_string(),
+        user: review_radar::User {
+            login: "alice".to_string(),
+        },
+        repo: "org1/api".to_string(),
+        ..Default::default()
+    });
+    checkpoint.save_to_path(&checkpoint_path).unwrap();
+
+    let mut cmd = Command::cargo_bin("rr").unwrap();
+    cmd.arg("--resume")
+        .xdg_dirs(&config_dir)
+        .env("PATH", &path)
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("Resuming scan"))
+        .stdout(predicate::str::contains("Api change"))
+        .stdout(predicate::str::contains("Web change"));
+
+    assert!(!fake_gh_calls(&temp_dir).contains("org1/api"));
+    assert!(!checkpoint_path.exists());
+
+    // PRs listed under another --pr-limit aren't reused
+    checkpoint.save_to_path(&checkpoint_path).unwrap();
+    let mut cmd = Command::cargo_bin("rr").unwrap();
+    cmd.args(["--resume", "--pr-limit", "5"])
+        .xdg_dirs(&config_dir)
+        .env("PATH", path)
+        .assert()
+        .success()
+        .stdout(predicate::str::contains(
+            "Previous scan used different settings",
+        ))
+        .stdout(predicate::str::contains("Api change").not())
+        .stdout(predicate::str::contains("Web change"));
+}
+
+#[test]
+fn test_delta_against_json_baseline() {
+    let temp_dir = TempDir::new().unwrap();
+    let config_dir = create_test_config(&temp_dir, vec!["org1"], "testuser", None);
+    let path = install_fake_gh(&temp_dir);
+    fake_gh_fixture(&temp_dir, "repos/org1.json", r#"[{"name":"api"}]"#);
+    fake_gh_fixture(
+        &temp_dir,
+        "prs/org1_api.json",
+        &format!("[{}]", review_requested_pr(1, "Old change", "testuser")),
+    );
+
+    // Save a snapshot as the baseline
+    let output = Command::cargo_bin("rr")
+        .unwrap()
+        .args(["--format", "json"])
+        .xdg_dirs(&config_dir)
+        .env("PATH", &path)
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+    let snapshot: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+    assert_eq!(snapshot["prs"][0]["title"], "Old change");
+    assert_eq!(snapshot["metadata"]["mode"], "review");
+    assert_eq!(snapshot["metadata"]["targets"][0]["orgs"][0], "org1");
+    assert_eq!(snapshot["metadata"]["targets"][0]["username"], "testuser");
+    let baseline_path = temp_dir.path().join("baseline.json");
+    fs::write(&baseline_path, &output.stdout).unwrap();
+
+    // The old PR was reviewed and a new one arrived
+    fake_gh_fixture(
+        &temp_dir,
+        "prs/org1_api.json",
+        &format!("[{}]", review_requested_pr(2, "New change", "testuser")),
+    );
+
+    let mut cmd = Command::cargo_bin("rr").unwrap();
+    cmd.args(["--format", "delta", "--baseline"])
+        .arg(&baseline_path)
+        .xdg_dirs(&config_dir)
+        .env("PATH", &path)
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("1 added, 1 removed"))
+        .stdout(predicate::str::contains("➕ #2 - New change"))
+        .stdout(predicate::str::contains("➖ #1 - Old change"));
+}
+
+#[test]
+fn test_delta_requires_baseline() {
+    let mut cmd = Command::cargo_bin("rr").unwrap();
+    cmd.args(["--format", "delta"])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("--baseline"));
+}
+
+#[test]
+fn test_scans_additional_hosts() {
+    let temp_dir = TempDir::new().unwrap();
+    let config_dir = create_test_config(&temp_dir, vec!["org1"], "testuser", None);
+    let config_path = std::path::Path::new(&config_dir)
+        .join("review-radar")
+        .join("config.toml");
+    let mut config = Config::load_from_path(&config_path).unwrap();
+    config.hosts.push(review_radar::HostConfig {
+        host: "github.example.com".to_string(),
+        orgs: vec!["corp".to_string()],
+        username: "corp-user".to_string(),
+    });
+    config.save_to_path(&config_path).unwrap();
+
+    let path = install_fake_gh(&temp_dir);
+    fake_gh_fixture(&temp_dir, "repos/org1.json", r#"[{"name":"api"}]"#);
+    fake_gh_fixture(&temp_dir, "repos/corp.json", r#"[{"name":"svc"}]"#);
+    fake_gh_fixture(
+        &temp_dir,
+        "prs/org1_api.json",
+        &format!("[{}]", review_requested_pr(1, "Public change", "testuser")),
+    );
+    fake_gh_fixture(
+        &temp_dir,
+        "prs/corp_svc.json",
+        &format!(
+            "[{}]",
+            review_requested_pr(2, "Internal change", "corp-user")
+        ),
+    );
+
+    let mut cmd = Command::cargo_bin("rr").unwrap();
+    cmd.xdg_dirs(&config_dir)
+        .env("PATH", path)
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("Found 2 PR(s)"))
+        .stdout(predicate::str::contains("Public change"))
+        .stdout(predicate::str::contains("Internal change"))
+        .stdout(predicate::str::contains("Host: github.example.com"));
+
+    let calls = fake_gh_calls(&temp_dir);
+    let host_searches: Vec<&str> = calls
+        .lines()
+        .filter(|line| line.starts_with("[github.example.com] search prs"))
+        .collect();
+    assert_eq!(host_searches.len(), 1, "{}", calls);
+    assert!(host_searches[0].ends_with("user-review-requested:corp-user org:corp"));
+}
+
+#[test]
+fn test_org_names_shown_in_progress() {
+    let temp_dir = TempDir::new().unwrap();
+    let config_dir = create_test_config(&temp_dir, vec!["acme-eng-2"], "testuser", None);
+    use_legacy_scan(&temp_dir);
+    let config_path = std::path::Path::new(&config_dir)
+        .join("review-radar")
+        .join("config.toml");
+    let mut config = Config::load_from_path(&config_path).unwrap();
+    config
+        .org_names
+        .insert("acme-eng-2".to_string(), "Acme Engineering".to_string());
+    config.save_to_path(&config_path).unwrap();
+
+    let path = install_fake_gh(&temp_dir);
+    fake_gh_fixture(&temp_dir, "repos/acme-eng-2.json", r#"[{"name":"api"}]"#);
+    fake_gh_fixture(
+        &temp_dir,
+        "prs/acme-eng-2_api.json",
+        &format!("[{}]", review_requested_pr(1, "Change", "testuser")),
+    );
+
+    let mut cmd = Command::cargo_bin("rr").unwrap();
+    cmd.arg("--verbose")
+        .xdg_dirs(&config_dir)
+        .env("PATH", &path)
+        .assert()
+        .success()
+        .stdout(predicate::str::contains(
+            "Searching for PRs in Acme Engineering",
+        ))
+        .stdout(predicate::str::contains("Fetching from Acme Engineering"))
+        .stdout(predicate::str::contains("📊 Acme Engineering: 1 PR(s)"));
+
+    // gh is still called with the org slug
+    let calls = fake_gh_calls(&temp_dir);
+    assert!(calls.contains("repo list acme-eng-2"), "{}", calls);
+    assert!(calls.contains("--repo acme-eng-2/api"), "{}", calls);
+}
+
+#[test]
+fn test_org_usernames_override_login_per_org() {
+    let temp_dir = TempDir::new().unwrap();
+    let config_dir = create_test_config(&temp_dir, vec!["org1", "org2"], "testuser", None);
+    let config_path = std::path::Path::new(&config_dir)
+        .join("review-radar")
+        .join("config.toml");
+    let mut config = Config::load_from_path(&config_path).unwrap();
+    config
+        .org_usernames
+        .insert("org2".to_string(), "test-alias".to_string());
+    config.save_to_path(&config_path).unwrap();
+
+    let path = install_fake_gh(&temp_dir);
+    fake_gh_fixture(&temp_dir, "repos/org1.json", r#"[{"name":"api"}]"#);
+    fake_gh_fixture(&temp_dir, "repos/org2.json", r#"[{"name":"web"}]"#);
+    fake_gh_fixture(
+        &temp_dir,
+        "prs/org1_api.json",
+        &format!(
+            "[{},{}]",
+            review_requested_pr(1, "Main account", "testuser"),
+            review_requested_pr(2, "Alias in org1", "test-alias")
+        ),
+    );
+    fake_gh_fixture(
+        &temp_dir,
+        "prs/org2_web.json",
+        &format!(
+            "[{},{}]",
+            review_requested_pr(3, "Main in org2", "testuser"),
+            review_requested_pr(4, "Alias account", "test-alias")
+        ),
+    );
+
+    let mut cmd = Command::cargo_bin("rr").unwrap();
+    cmd.arg("--verbose")
+        .xdg_dirs(&config_dir)
+        .env("PATH", &path)
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("Matching as testuser in org1"))
+        .stdout(predicate::str::contains("Matching as test-alias in org2"))
+        .stdout(predicate::str::contains("Found 2 PR(s)"))
+        .stdout(predicate::str::contains("Main account"))
+        .stdout(predicate::str::contains("Alias account"));
+
+    // An explicit --username applies everywhere
+    let mut cmd = Command::cargo_bin("rr").unwrap();
+    cmd.args(["--username", "testuser"])
+        .xdg_dirs(&config_dir)
+        .env("PATH", &path)
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("Main in org2"));
+}
+
+#[test]
+fn test_repeated_repos_scanned_once() {
+    let temp_dir = TempDir::new().unwrap();
+    let config_dir = create_test_config(&temp_dir, vec!["org1", "ORG1"], "testuser", None);
+    let config_path = std::path::Path::new(&config_dir)
+        .join("review-radar")
+        .join("config.toml");
+    let mut config = Config::load_from_path(&config_path).unwrap();
+    config.repos.insert(
+        "org1".to_string(),
+        vec!["api".to_string(), "API".to_string()],
+    );
+    config.save_to_path(&config_path).unwrap();
+
+    let path = install_fake_gh(&temp_dir);
+    fake_gh_fixture(&temp_dir, "repos/ORG1.json", r#"[{"name":"api"}]"#);
+    fake_gh_fixture(
+        &temp_dir,
+        "prs/org1_api.json",
+        &format!("[{}]", review_requested_pr(1, "Once", "testuser")),
+    );
+
+    let mut cmd = Command::cargo_bin("rr").unwrap();
+    cmd.args(["--format", "urls"])
+        .xdg_dirs(&config_dir)
+        .env("PATH", &path)
+        .assert()
+        .success()
+        .stdout("https://github.com/pull/1\n");
+
+    let calls = fake_gh_calls(&temp_dir);
+    assert_eq!(calls.matches("pr list").count(), 1, "{}", calls);
+}
+
+#[test]
+fn test_pinned_repos_skip_listing() {
+    let temp_dir = TempDir::new().unwrap();
+    let config_dir = create_test_config(&temp_dir, vec!["org1", "org2"], "testuser", None);
+    let config_path = std::path::Path::new(&config_dir)
+        .join("review-radar")
+        .join("config.toml");
+    let mut config = Config::load_from_path(&config_path).unwrap();
+    config
+        .repos
+        .insert("org1".to_string(), vec!["api".to_string()]);
+    config.save_to_path(&config_path).unwrap();
+
+    let path = install_fake_gh(&temp_dir);
+    fake_gh_fixture(&temp_dir, "repos/org2.json", r#"[{"name":"web"}]"#);
+    fake_gh_fixture(
+        &temp_dir,
+        "prs/org1_api.json",
+        &format!("[{}]", review_requested_pr(1, "Pinned", "testuser")),
+    );
+
+    let mut cmd = Command::cargo_bin("rr").unwrap();
+    cmd.xdg_dirs(&config_dir)
+        .env("PATH", &path)
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("Pinned"));
+
+    let calls = fake_gh_calls(&temp_dir);
+    assert!(!calls.contains("repo list org1"));
+    assert!(calls.contains("repo list org2"));
+    assert!(calls.contains("pr list --repo org1/api"));
+    assert!(calls.contains("pr list --repo org2/web"));
+}
+
+#[test]
+fn test_broad_scan_needs_confirmation() {
+    let temp_dir = TempDir::new().unwrap();
+    let config_dir = create_test_config(&temp_dir, vec!["org1"], "testuser", None);
+    use_legacy_scan(&temp_dir);
+    let path = install_fake_gh(&temp_dir);
+    let repos: Vec<String> = (0..501)
+        .map(|i| format!(r#"{{"name":"repo{}"}}"#, i))
+        .collect();
+    fake_gh_fixture(
+        &temp_dir,
+        "repos/org1.json",
+        &format!("[{}]", repos.join(",")),
+    );
+
+    let mut cmd = Command::cargo_bin("rr").unwrap();
+    cmd.xdg_dirs(&config_dir)
+        .env("PATH", &path)
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains(
+            "Refusing to scan 501 repositories without confirmation",
+        ));
+    assert!(!fake_gh_calls(&temp_dir).contains("pr list"));
+
+    let mut cmd = Command::cargo_bin("rr").unwrap();
+    cmd.arg("--yes")
+        .xdg_dirs(&config_dir)
+        .env("PATH", &path)
+        .assert()
+        .success();
+    assert_eq!(fake_gh_calls(&temp_dir).matches("pr list").count(), 501);
+}
+
+#[test]
+fn test_sample_scans_subset() {
+    let temp_dir = TempDir::new().unwrap();
+    let config_dir = create_test_config(&temp_dir, vec!["org1"], "testuser", None);
+    let path = install_fake_gh(&temp_dir);
+    fake_gh_fixture(
+        &temp_dir,
+        "repos/org1.json",
+        r#"[{"name":"a"},{"name":"b"},{"name":"c"}]"#,
+    );
+
+    let mut cmd = Command::cargo_bin("rr").unwrap();
+    cmd.args(["--sample", "2", "--seed", "1"])
+        .xdg_dirs(&config_dir)
+        .env("PATH", path)
+        .assert()
+        .success()
+        .stdout(predicate::str::contains(
+            "Sampling 2 of 3 repositories (seed 1)",
+        ));
+
+    assert_eq!(fake_gh_calls(&temp_dir).matches("pr list").count(), 2);
+}
+
+#[test]
+fn test_visibility_filter() {
+    let temp_dir = TempDir::new().unwrap();
+    let config_dir = create_test_config(&temp_dir, vec!["org1"], "testuser", None);
+    let path = install_fake_gh(&temp_dir);
+    fake_gh_fixture(
+        &temp_dir,
+        "repos/org1.json",
+        r#"[{"name":"oss","visibility":"PUBLIC"},{"name":"infra","visibility":"PRIVATE"}]"#,
+    );
+
+    let mut cmd = Command::cargo_bin("rr").unwrap();
+    cmd.args(["--visibility", "private"])
+        .xdg_dirs(&config_dir)
+        .env("PATH", path)
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("1 of them are private"));
+
+    let calls = fake_gh_calls(&temp_dir);
+    assert!(calls.contains("--json name,visibility"));
+    assert!(calls.contains("pr list --repo org1/infra"));
+    assert!(!calls.contains("org1/oss"));
+}
+
+#[test]
+fn test_own_conflicts_only() {
+    let temp_dir = TempDir::new().unwrap();
+    let config_dir = create_test_config(&temp_dir, vec!["org1"], "testuser", None);
+    let path = install_fake_gh(&temp_dir);
+    fake_gh_fixture(&temp_dir, "repos/org1.json", r#"[{"name":"api"}]"#);
+    fake_gh_fixture(
+        &temp_dir,
+        "prs/org1_api.json",
+        r#"[
+            {"number":1,"title":"Clean","url":"u1","author":{"login":"testuser"},"mergeable":"MERGEABLE"},
+            {"number":2,"title":"Needs rebase","url":"u2","author":{"login":"testuser"},"mergeable":"CONFLICTING"}
+        ]"#,
+    );
+
+    let mut cmd = Command::cargo_bin("rr").unwrap();
+    cmd.args(["--own", "--conflicts-only"])
+        .xdg_dirs(&config_dir)
+        .env("PATH", path)
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("#2 - Needs rebase ⚠️  conflicts"))
+        .stdout(predicate::str::contains("Clean").not());
+
+    assert!(fake_gh_calls(&temp_dir).contains("mergeable,mergeStateStatus"));
+}
+
+#[test]
+fn test_team_only() {
+    let temp_dir = TempDir::new().unwrap();
+    let config_dir = create_test_config(&temp_dir, vec!["org1"], "testuser", None);
+    let path = install_fake_gh(&temp_dir);
+    fake_gh_fixture(&temp_dir, "repos/org1.json", r#"[{"name":"api"}]"#);
+    fake_gh_fixture(&temp_dir, "api/user_teams", "org1/core\n");
+    fake_gh_fixture(
+        &temp_dir,
+        "prs/org1_api.json",
+        r#"[
+            {"number":1,"title":"Direct","url":"u1","author":{"login":"alice"},
+             "reviewRequests":[{"__typename":"User","login":"testuser"}]},
+            {"number":2,"title":"Via core","url":"u2","author":{"login":"alice"},
+             "reviewRequests":[{"__typename":"Team","slug":"core"}]},
+            {"number":3,"title":"Via other team","url":"u3","author":{"login":"alice"},
+             "reviewRequests":[{"__typename":"Team","slug":"design"}]}
+        ]"#,
+    );
+
+    let mut cmd = Command::cargo_bin("rr").unwrap();
+    cmd.arg("--team-only")
+        .xdg_dirs(&config_dir)
+        .env("PATH", path)
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("Found 1 PR(s)"))
+        .stdout(predicate::str::contains("#2 - Via core"))
+        .stdout(predicate::str::contains("Requested via team: org1/core"));
+
+    assert!(!fake_gh_calls(&temp_dir).contains("--jq {total"));
+}
+
+#[test]
+fn test_page_size() {
+    let temp_dir = TempDir::new().unwrap();
+    let config_dir = create_test_config(&temp_dir, vec!["org1"], "testuser", None);
+    let path = install_fake_gh(&temp_dir);
+    fake_gh_fixture(&temp_dir, "repos/org1.json", r#"[{"name":"api"}]"#);
+    fake_gh_fixture(&temp_dir, "prs/org1_api.json", "[]");
+    fake_gh_fixture(&temp_dir, "api/user_teams?per_page=100", "org1/core\n");
+
+    let mut cmd = Command::cargo_bin("rr").unwrap();
+    cmd.args(["--team-only", "--page-size", "100"])
+        .xdg_dirs(&config_dir)
+        .env("PATH", &path)
+        .assert()
+        .success();
+    assert!(fake_gh_calls(&temp_dir).contains("api user/teams?per_page=100 --paginate"));
+
+    for size in ["0", "101"] {
+        let mut cmd = Command::cargo_bin("rr").unwrap();
+        cmd.args(["--page-size", size])
+            .xdg_dirs(&config_dir)
+            .env("PATH", &path)
+            .assert()
+            .failure()
+            .stderr(predicate::str::contains("1..=100"));
+    }
+}
+
+#[test]
+fn test_include_closed_requests() {
+    let temp_dir = TempDir::new().unwrap();
+    let config_dir = create_test_config(&temp_dir, vec!["org1"], "testuser", None);
+    let path = install_fake_gh(&temp_dir);
+    fake_gh_fixture(&temp_dir, "repos/org1.json", r#"[{"name":"api"}]"#);
+    fake_gh_fixture(
+        &temp_dir,
+        "prs/org1_api.json",
+        r#"[
+            {"number":1,"title":"Still open","url":"u1","author":{"login":"alice"},
+             "state":"OPEN","reviewRequests":[{"login":"testuser"}]},
+            {"number":2,"title":"Merged anyway","url":"u2","author":{"login":"alice"},
+             "state":"MERGED","reviewRequests":[{"login":"testuser"}]}
+        ]"#,
+    );
+
+    let mut cmd = Command::cargo_bin("rr").unwrap();
+    cmd.arg("--include-closed-requests")
+        .xdg_dirs(&config_dir)
+        .env("PATH", path)
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("Found 2 PR(s)"))
+        .stdout(predicate::str::contains(
+            "#2 - Merged anyway\n   👤 Author: alice\n   🔒 merged without your review",
+        ))
+        .stdout(predicate::str::contains("closed without your review").not());
+
+    let calls = fake_gh_calls(&temp_dir);
+    assert!(calls.contains("reviewRequests,createdAt,state --state all"));
+}
+
+#[test]
+fn test_pr_limit_warns_when_reached() {
+    let temp_dir = TempDir::new().unwrap();
+    let config_dir = create_test_config(&temp_dir, vec!["org1"], "testuser", None);
+    use_legacy_scan(&temp_dir);
+    let path = install_fake_gh(&temp_dir);
+    fake_gh_fixture(
+        &temp_dir,
+        "repos/org1.json",
+        r#"[{"name":"api"},{"name":"web"}]"#,
+    );
+    fake_gh_fixture(
+        &temp_dir,
+        "prs/org1_api.json",
+        &format!(
+            r#"[{}, {{"number":2,"title":"Other","url":"u2","author":{{"login":"bob"}},"reviewRequests":[]}}]"#,
+            review_requested_pr(1, "Change", "testuser")
+        ),
+    );
+    fake_gh_fixture(&temp_dir, "prs/org1_web.json", "[]");
+
+    let mut cmd = Command::cargo_bin("rr").unwrap();
+    cmd.args(["--pr-limit", "2"])
+        .xdg_dirs(&config_dir)
+        .env("PATH", &path)
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("Found 1 PR(s)"))
+        .stderr(predicate::str::contains(
+            "org1/api has at least 2 PRs, so some may be missing",
+        ))
+        .stderr(predicate::str::contains("org1/web").not());
+
+    assert!(fake_gh_calls(&temp_dir).contains("pr list --repo org1/api --limit 2 "));
+}
+
+#[test]
+fn test_pr_limit_applies_to_search() {
+    let temp_dir = TempDir::new().unwrap();
+    let config_dir = create_test_config(&temp_dir, vec!["org1"], "testuser", None);
+    let path = install_fake_gh(&temp_dir);
+    fake_gh_fixture(
+        &temp_dir,
+        "prs/org1_api.json",
+        &format!(
+            "[{}, {}, {}]",
+            review_requested_pr(1, "First", "testuser"),
+            review_requested_pr(2, "Second", "testuser"),
+            review_requested_pr(3, "Third", "testuser")
+        ),
+    );
+
+    let mut cmd = Command::cargo_bin("rr").unwrap();
+    cmd.args(["--pr-limit", "2"])
+        .xdg_dirs(&config_dir)
+        .env("PATH", &path)
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("Found 2 PR(s)"))
+        .stderr(predicate::str::contains(
+            "The search found at least 2 PRs, so some may be missing (raise --pr-limit)",
+        ));
+
+    assert!(fake_gh_calls(&temp_dir).contains(" --limit 2 -- "));
+}
+
+#[test]
+fn test_self_requested_review_is_excluded() {
+    let temp_dir = TempDir::new().unwrap();
+    let config_dir = create_test_config(&temp_dir, vec!["org1"], "testuser", None);
+    let path = install_fake_gh(&temp_dir);
+    fake_gh_fixture(&temp_dir, "repos/org1.json", r#"[{"name":"api"}]"#);
+    fake_gh_fixture(
+        &temp_dir,
+        "prs/org1_api.json",
+        r#"[
+            {"number":1,"title":"Mine","url":"u1","author":{"login":"testuser"},
+             "reviewRequests":[{"login":"testuser"}]},
+            {"number":2,"title":"Theirs","url":"u2","author":{"login":"alice"},
+             "reviewRequests":[{"login":"testuser"}]}
+        ]"#,
+    );
+
+    for extra in [None, Some("--legacy-scan")] {
+        let mut cmd = Command::cargo_bin("rr").unwrap();
+        cmd.args(extra)
+            .xdg_dirs(&config_dir)
+            .env("PATH", &path)
+            .assert()
+            .success()
+            .stdout(predicate::str::contains("Found 1 PR(s)"))
+            .stdout(predicate::str::contains("#2 - Theirs"))
+            .stdout(predicate::str::contains("Mine").not());
+    }
+
+    assert!(fake_gh_calls(&temp_dir).contains("--jq {total: length, prs: map("));
+}
+
+#[test]
+fn test_config_defaults_apply_unless_overridden() {
+    let temp_dir = TempDir::new().unwrap();
+    let config_dir = create_test_config(&temp_dir, vec!["org1"], "testuser", None);
+    let config_path = std::path::Path::new(&config_dir)
+        .join("review-radar")
+        .join("config.toml");
+    let mut config = Config::load_from_path(&config_path).unwrap();
+    config.defaults.format = Some(review_radar::format::OutputFormat::Json);
+    config.save_to_path(&config_path).unwrap();
+
+    let path = install_fake_gh(&temp_dir);
+    fake_gh_fixture(&temp_dir, "repos/org1.json", r#"[{"name":"api"}]"#);
+    fake_gh_fixture(
+        &temp_dir,
+        "prs/org1_api.json",
+        &format!("[{}]", review_requested_pr(1, "Change", "testuser")),
+    );
+
+    let output = Command::cargo_bin("rr")
+        .unwrap()
+        .xdg_dirs(&config_dir)
+        .env("PATH", &path)
+        .output()
+        .unwrap();
+    let prs: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+    assert_eq!(prs["prs"][0]["title"], "Change");
+
+    let mut cmd = Command::cargo_bin("rr").unwrap();
+    cmd.args(["--format", "text"])
+        .xdg_dirs(&config_dir)
+        .env("PATH", &path)
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("🔗 #1 - Change"));
+}
+
+#[test]
+fn test_me_username_is_resolved_and_cached() {
+    let temp_dir = TempDir::new().unwrap();
+    let config_dir = create_test_config(&temp_dir, vec!["org1"], "@me", None);
+    let path = install_fake_gh(&temp_dir);
+    fake_gh_fixture(&temp_dir, "api/user", "testuser\n");
+    fake_gh_fixture(&temp_dir, "repos/org1.json", r#"[{"name":"api"}]"#);
+    fake_gh_fixture(
+        &temp_dir,
+        "prs/org1_api.json",
+        &format!("[{}]", review_requested_pr(1, "For me", "testuser")),
+    );
+
+    let run = |extra: &[&str]| {
+        let mut cmd = Command::cargo_bin("rr").unwrap();
+        cmd.args(extra)
+            .xdg_dirs(&config_dir)
+            .env("PATH", &path)
+            .assert()
+            .success()
+            .stdout(predicate::str::contains(
+                "where testuser has been requested",
+            ))
+            .stdout(predicate::str::contains("For me"));
+    };
+
+    run(&[]);
+    run(&[]);
+    assert_eq!(fake_gh_calls(&temp_dir).matches("api user").count(), 1);
+
+    run(&["--refresh-identity"]);
+    assert_eq!(fake_gh_calls(&temp_dir).matches("api user").count(), 2);
+}
+
+#[test]
+fn test_auth_check_retries_network_errors() {
+    let temp_dir = TempDir::new().unwrap();
+    let config_dir = create_test_config(&temp_dir, vec!["org1"], "testuser", None);
+    let path = install_fake_gh(&temp_dir);
+    fake_gh_fixture(
+        &temp_dir,
+        "auth_error",
+        "error connecting to api.github.com\n",
+    );
+
+    let mut cmd = Command::cargo_bin("rr").unwrap();
+    cmd.xdg_dirs(&config_dir)
+        .env("PATH", &path)
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("retrying"))
+        .stderr(predicate::str::contains("Could not reach GitHub"));
+    assert_eq!(fake_gh_calls(&temp_dir).matches("auth status").count(), 2);
+}
+
+#[test]
+fn test_auth_check_does_not_retry_when_logged_out() {
+    let temp_dir = TempDir::new().unwrap();
+    let config_dir = create_test_config(&temp_dir, vec!["org1"], "testuser", None);
+    let path = install_fake_gh(&temp_dir);
+    fake_gh_fixture(
+        &temp_dir,
+        "auth_error",
+        "You are not logged into any GitHub hosts.\n",
+    );
+
+    let mut cmd = Command::cargo_bin("rr").unwrap();
+    cmd.xdg_dirs(&config_dir)
+        .env("PATH", &path)
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("not authenticated"));
+    assert_eq!(fake_gh_calls(&temp_dir).matches("auth status").count(), 1);
+}
+
+#[test]
+fn test_healthcheck() {
+    let temp_dir = TempDir::new().unwrap();
+    let config_dir = create_test_config(&temp_dir, vec!["empty", "org1"], "testuser", None);
+    let path = install_fake_gh(&temp_dir);
+    fake_gh_fixture(&temp_dir, "repos/empty.json", "[]");
+    fake_gh_fixture(&temp_dir, "repos/org1.json", r#"[{"name":"api"}]"#);
+    fake_gh_fixture(&temp_dir, "prs/org1_api.json", "[]");
+
+    let mut cmd = Command::cargo_bin("rr").unwrap();
+    cmd.arg("healthcheck")
+        .xdg_dirs(&config_dir)
+        .env("PATH", &path)
+        .assert()
+        .success()
+        .stdout("OK: gh authenticated, listed org1/api and its PRs\n");
+
+    fs::remove_file(temp_dir.path().join("bin/prs/org1_api.json")).unwrap();
+    let mut cmd = Command::cargo_bin("rr").unwrap();
+    cmd.arg("healthcheck")
+        .xdg_dirs(&config_dir)
+        .env("PATH", &path)
+        .assert()
+        .failure()
+        .stdout(predicate::str::starts_with(
+            "FAIL: could not list PRs for org1/api",
+        ));
+}
+
+#[test]
+fn test_healthcheck_suspended_account() {
+    let temp_dir = TempDir::new().unwrap();
+    let config_dir = create_test_config(&temp_dir, vec!["org1"], "testuser", None);
+    let path = install_fake_gh(&temp_dir);
+    fake_gh_fixture(&temp_dir, "repos/org1.json", r#"[{"name":"api"}]"#);
+    fake_gh_fixture(&temp_dir, "prs/org1_api.json", "[]");
+    fake_gh_fixture(&temp_dir, "api/users_testuser", "\n");
+
+    let mut cmd = Command::cargo_bin("rr").unwrap();
+    cmd.arg("healthcheck")
+        .xdg_dirs(&config_dir)
+        .env("PATH", &path)
+        .assert()
+        .success();
+
+    fake_gh_fixture(&temp_dir, "api/users_testuser", "2024-03-01T09:00:00Z\n");
+    let mut cmd = Command::cargo_bin("rr").unwrap();
+    cmd.arg("healthcheck")
+        .xdg_dirs(&config_dir)
+        .env("PATH", &path)
+        .assert()
+        .failure()
+        .stdout(predicate::str::starts_with(
+            "FAIL: the account 'testuser' was suspended on 2024-03-01",
+        ));
+}
+
+#[test]
+fn test_verbose_reports_search_summary() {
+    let temp_dir = TempDir::new().unwrap();
+    let config_dir = create_test_config(&temp_dir, vec!["org1"], "testuser", None);
+    use_legacy_scan(&temp_dir);
+    let path = install_fake_gh(&temp_dir);
+    fake_gh_fixture(
+        &temp_dir,
+        "repos/org1.json",
+        r#"[{"name":"api"},{"name":"locked"}]"#,
+    );
+    fake_gh_fixture(
+        &temp_dir,
+        "prs/org1_api.json",
+        &format!("[{}]", review_requested_pr(1, "Change", "testuser")),
+    );
+
+    let mut cmd = Command::cargo_bin("rr").unwrap();
+    cmd.arg("--verbose")
+        .xdg_dirs(&config_dir)
+        .env("PATH", &path)
+        .assert()
+        .success()
+        .stdout(predicate::str::contains(
+            "1 of 2 repositories had review requests",
+        ))
+        .stdout(predicate::str::contains("scanned 2 in"))
+        .stdout(predicate::str::contains(
+            "Skipped 1 inaccessible repositories: org1/locked",
+        ))
+        .stdout(predicate::str::contains("org1: 1 PR(s)"));
+}
+
+#[test]
+fn test_verbose_reports_github_searches() {
+    let temp_dir = TempDir::new().unwrap();
+    let config_dir = create_test_config(&temp_dir, vec!["org1"], "testuser", None);
+    let path = install_fake_gh(&temp_dir);
+    fake_gh_fixture(
+        &temp_dir,
+        "prs/org1_api.json",
+        &format!("[{}]", review_requested_pr(1, "Change", "testuser")),
+    );
+    fake_gh_fixture(&temp_dir, "prs/org1_web.json", "[]");
+
+    let mut cmd = Command::cargo_bin("rr").unwrap();
+    cmd.arg("--verbose")
+        .xdg_dirs(&config_dir)
+        .env("PATH", &path)
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("Ran 1 GitHub search in "))
+        .stdout(predicate::str::contains("finding PRs in 1 repositories"))
+        .stdout(predicate::str::contains("Listed repositories").not());
+}
+
+#[test]
+fn test_query_uses_github_search() {
+    let temp_dir = TempDir::new().unwrap();
+    let config_dir = create_test_config(&temp_dir, vec!["org1"], "testuser", None);
+    let path = install_fake_gh(&temp_dir);
+    fake_gh_fixture(
+        &temp_dir,
+        "search.json",
+        r#"[{"number":9,"title":"Found by search","url":"https://github.com/other/lib/pull/9",
+             "author":{"login":"bob"},"repository":{"name":"lib","nameWithOwner":"other/lib"}}]"#,
+    );
+
+    let mut cmd = Command::cargo_bin("rr").unwrap();
+    cmd.args([
+        "--query",
+        "review-requested:@me -label:wip",
+        "--format",
+        "paths",
+    ])
+    .xdg_dirs(&config_dir)
+    .env("PATH", &path)
+    .assert()
+    .success()
+    .stdout("other/lib\n");
+
+    let calls = fake_gh_calls(&temp_dir);
+    assert!(calls.contains("search prs --json number,title,url,author,repository --limit 1000 -- review-requested:@me -label:wip"));
+    assert!(!calls.contains("repo list"));
+}
+
+#[test]
+fn test_review_requests_via_search() {
+    let temp_dir = TempDir::new().unwrap();
+    let config_dir = create_test_config(&temp_dir, vec!["org1"], "testuser", Some("^a"));
+    let path = install_fake_gh(&temp_dir);
+    fake_gh_fixture(
+        &temp_dir,
+        "search.json",
+        r#"[{"number":4,"title":"Web","url":"https://github.com/org1/web/pull/4",
+             "author":{"login":"bob"},"repository":{"name":"web","nameWithOwner":"org1/web"}},
+            {"number":7,"title":"Api","url":"https://github.com/ORG1/api/pull/7",
+             "author":{"login":"bob"},"repository":{"name":"api","nameWithOwner":"ORG1/api"}}]"#,
+    );
+    fake_gh_fixture(&temp_dir, "repos/org1.json", r#"[{"name":"api"}]"#);
+    fake_gh_fixture(
+        &temp_dir,
+        "prs/org1_api.json",
+        &format!("[{}]", review_requested_pr(1, "Change", "testuser")),
+    );
+
+    let run = |extra: &[&str]| {
+        Command::cargo_bin("rr")
+            .unwrap()
+            .args(["--format", "paths"])
+            .args(extra)
+            .xdg_dirs(&config_dir)
+            .env("PATH", &path)
+            .output()
+            .unwrap()
+    };
+
+    // One search for the org; the pattern drops org1/web, and the org keeps
+    // the configured spelling
+    let output = run(&[]);
+    assert!(output.status.success());
+    assert_eq!(String::from_utf8_lossy(&output.stdout), "org1/api\n");
+    let calls = fake_gh_calls(&temp_dir);
+    assert!(calls.contains("search prs --json number,title,url,author,repository --limit 1000 -- is:pr is:open user-review-requested:testuser org:org1"));
+    assert!(!calls.contains("repo list"));
+    assert!(!calls.contains("pr list"));
+
+    // --legacy-scan and settings the search can't serve list each repository
+    for extra in [&["--legacy-scan"][..], &["--conflicts-only"]] {
+        fs::write(temp_dir.path().join("bin/calls.log"), "").unwrap();
+        let output = run(extra);
+        assert!(output.status.success());
+        let calls = fake_gh_calls(&temp_dir);
+        assert!(calls.contains("pr list --repo org1/api"));
+        assert!(!calls.contains("search prs"));
+    }
+    assert!(String::from_utf8_lossy(&run(&["--conflicts-only"]).stderr).contains(
+        "--conflicts-only needs each repository's PRs, so they're listed one repository at a time"
+    ));
+}
+
+#[test]
+fn test_token_file_is_passed_to_gh() {
+    let temp_dir = TempDir::new().unwrap();
+    let config_dir = create_test_config(&temp_dir, vec!["org1"], "testuser", None);
+    let path = install_fake_gh(&temp_dir);
+    fake_gh_fixture(&temp_dir, "repos/org1.json", r#"[{"name":"api"}]"#);
+    let token_path = temp_dir.path().join("token");
+    fs::write(&token_path, "ghp_fromfile\n").unwrap();
+
+    let mut cmd = Command::cargo_bin("rr").unwrap();
+    cmd.xdg_dirs(&config_dir)
+        .env("PATH", &path)
+        .env("REVIEW_RADAR_TOKEN_FILE", &token_path)
+        .assert()
+        .success();
+
+    let calls = fake_gh_calls(&temp_dir);
+    assert!(calls.contains("<ghp_fromfile> auth status"));
+    assert!(calls.contains("<ghp_fromfile> search prs"));
+
+    let mut cmd = Command::cargo_bin("rr").unwrap();
+    cmd.args(["--token-file", "/nonexistent/token"])
+        .xdg_dirs(&config_dir)
+        .env("PATH", &path)
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("Could not read token file"));
+}
+
+#[test]
+fn test_kanban_groups_own_prs_by_review_decision() {
+    let temp_dir = TempDir::new().unwrap();
+    let config_dir = create_test_config(&temp_dir, vec!["org1"], "testuser", None);
+    let path = install_fake_gh(&temp_dir);
+    fake_gh_fixture(&temp_dir, "repos/org1.json", r#"[{"name":"api"}]"#);
+    fake_gh_fixture(
+        &temp_dir,
+        "prs/org1_api.json",
+        r#"[
+            {"number":1,"title":"Waiting","url":"u1","author":{"login":"testuser"},"reviewDecision":"REVIEW_REQUIRED"},
+            {"number":2,"title":"Needs work","url":"u2","author":{"login":"testuser"},"reviewDecision":"CHANGES_REQUESTED"}
+        ]"#,
+    );
+
+    let mut cmd = Command::cargo_bin("rr").unwrap();
+    cmd.args(["--own", "--format", "kanban"])
+        .xdg_dirs(&config_dir)
+        .env("PATH", &path)
+        .assert()
+        .success()
+        .stdout(predicate::str::contains(
+            "📝 Review required (1)\n   #1 - Waiting",
+        ))
+        .stdout(predicate::str::contains(
+            "🔁 Changes requested (1)\n   #2 - Needs work",
+        ))
+        .stdout(predicate::str::contains("✅ Approved (0)"));
+
+    assert!(fake_gh_calls(&temp_dir).contains("reviewDecision"));
+}
+
+#[test]
+fn test_skip_org() {
+    let temp_dir = TempDir::new().unwrap();
+    let config_dir = create_test_config(&temp_dir, vec!["org1", "org2", "org3"], "testuser", None);
+    let path = install_fake_gh(&temp_dir);
+
+    let mut cmd = Command::cargo_bin("rr").unwrap();
+    cmd.args([
+        "--skip-org",
+        "org1",
+        "--skip-org",
+        "org3",
+        "--skip-org",
+        "nope",
+    ])
+    .xdg_dirs(&config_dir)
+    .env("PATH", &path)
+    .assert()
+    .success()
+    .stderr(predicate::str::contains("--skip-org nope: not among"));
+
+    let calls = fake_gh_calls(&temp_dir);
+    assert!(calls.contains("org:org2"));
+    assert!(!calls.contains("org:org1"));
+    assert!(!calls.contains("repo list org3"));
+}
+
+#[test]
+fn test_repo_cache_refilters_on_pattern_change() {
+    let temp_dir = TempDir::new().unwrap();
+    let config_dir = create_test_config(&temp_dir, vec!["org1"], "testuser", None);
+    use_legacy_scan(&temp_dir);
+    let path = install_fake_gh(&temp_dir);
+    fake_gh_fixture(
+        &temp_dir,
+        "repos/org1.json",
+        r#"[{"name":"api"},{"name":"web"},{"name":"docs"}]"#,
+    );
+
+    let run = |extra: &[&str], expected: &str| {
+        let mut cmd = Command::cargo_bin("rr").unwrap();
+        cmd.args(extra)
+            .xdg_dirs(&config_dir)
+            .env("PATH", &path)
+            .assert()
+            .success()
+            .stdout(predicate::str::contains(expected.to_string()));
+    };
+
+    run(
+        &["-r", "^api$"],
+        "found 1 repositories matching pattern '^api$'",
+    );
+    // Served from the cache, but filtered from the full list again
+    run(
+        &["-r", "^(api|web)$"],
+        "found 2 repositories matching pattern",
+    );
+    run(&[], "found 3 repositories");
+    assert_eq!(fake_gh_calls(&temp_dir).matches("repo list").count(), 1);
+
+    run(&["--refresh-repos"], "found 3 repositories");
+    assert_eq!(fake_gh_calls(&temp_dir).matches("repo list").count(), 2);
+}
+
+#[test]
+fn test_summary_only() {
+    let temp_dir = TempDir::new().unwrap();
+    let config_dir = create_test_config(&temp_dir, vec!["org1"], "testuser", None);
+    let path = install_fake_gh(&temp_dir);
+    fake_gh_fixture(&temp_dir, "repos/org1.json", r#"[{"name":"api"}]"#);
+    fake_gh_fixture(
+        &temp_dir,
+        "prs/org1_api.json",
+        r#"[{"number":1,"title":"Old","url":"u1","author":{"login":"alice"},
+             "reviewRequests":[{"login":"testuser"}],"createdAt":"2020-01-01T00:00:00Z"}]"#,
+    );
+
+    let mut cmd = Command::cargo_bin("rr").unwrap();
+    cmd.args(["--format", "summary-only"])
+        .xdg_dirs(&config_dir)
+        .env("PATH", &path)
+        .assert()
+        .success()
+        .stdout(
+            predicate::str::is_match(r"^You have 1 PR awaiting review \(oldest \d+ days\)\n$")
+                .unwrap(),
+        );
+
+    assert!(fake_gh_calls(&temp_dir).contains("createdAt"));
+}
+
+#[test]
+fn test_obsidian_format() {
+    let temp_dir = TempDir::new().unwrap();
+    let config_dir = create_test_config(&temp_dir, vec!["org1"], "testuser", None);
+    let path = install_fake_gh(&temp_dir);
+    fake_gh_fixture(&temp_dir, "repos/org1.json", r#"[{"name":"api"}]"#);
+    fake_gh_fixture(
+        &temp_dir,
+        "prs/org1_api.json",
+        r#"[{"number":1,"title":"Fix: login","url":"u1","author":{"login":"alice"},
+             "reviewRequests":[{"login":"testuser"}],"createdAt":"2020-01-01T00:00:00Z"}]"#,
+    );
+
+    let mut cmd = Command::cargo_bin("rr").unwrap();
+    cmd.args(["--format", "obsidian"])
+        .xdg_dirs(&config_dir)
+        .env("PATH", &path)
+        .assert()
+        .success()
+        .stdout(predicate::str::is_match(r"^---\ndate: \d{4}-\d{2}-\d{2}\nprs: 1\n").unwrap())
+        .stdout(predicate::str::contains(
+            "- [ ] [[api-1 Fix login]] — [org1/api#1](u1) by alice, opened 2020-01-01\n",
+        ));
+}
+
+#[test]
+fn test_default_mode_from_config() {
+    let temp_dir = TempDir::new().unwrap();
+    let config_dir = create_test_config(&temp_dir, vec!["org1"], "testuser", None);
+    let config_path = std::path::Path::new(&config_dir)
+        .join("review-radar")
+        .join("config.toml");
+    let mut config = Config::load_from_path(&config_path).unwrap();
+    config.defaults.mode = Some(review_radar::SearchMode::Own);
+    config.save_to_path(&config_path).unwrap();
+
+    let path = install_fake_gh(&temp_dir);
+    fake_gh_fixture(&temp_dir, "repos/org1.json", r#"[{"name":"api"}]"#);
+    fake_gh_fixture(
+        &temp_dir,
+        "prs/org1_api.json",
+        &format!("[{}]", review_requested_pr(1, "Change", "testuser")),
+    );
+
+    let mut cmd = Command::cargo_bin("rr").unwrap();
+    cmd.xdg_dirs(&config_dir)
+        .env("PATH", &path)
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("PR(s) you have open"));
+    assert_eq!(
+        fake_gh_calls(&temp_dir)
+            .matches("--author testuser")
+            .count(),
+        1
+    );
+
+    let mut cmd = Command::cargo_bin("rr").unwrap();
+    cmd.args(["--mode", "all"])
+        .xdg_dirs(&config_dir)
+        .env("PATH", &path)
+        .assert()
+        .success()
+        .stdout(predicate::str::contains(
+            "PR(s) requesting your review or opened by you",
+        ));
+    let calls = fake_gh_calls(&temp_dir);
+    assert_eq!(calls.matches("--author testuser").count(), 2);
+    assert_eq!(calls.matches("search prs").count(), 1);
+
+    let mut cmd = Command::cargo_bin("rr").unwrap();
+    cmd.args(["--mode", "own", "--team-only"])
+        .xdg_dirs(&config_dir)
+        .env("PATH", &path)
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("only apply to review requests"));
+}
+
+#[test]
+#[cfg(target_os = "linux")]
+fn test_notify_summary() {
+    use std::os::unix::fs::PermissionsExt;
+
+    let temp_dir = TempDir::new().unwrap();
+    let config_dir = create_test_config(&temp_dir, vec!["org1"], "testuser", None);
+    let path = install_fake_gh(&temp_dir);
+    fake_gh_fixture(&temp_dir, "repos/org1.json", r#"[{"name":"api"}]"#);
+    fake_gh_fixture(
+        &temp_dir,
+        "prs/org1_api.json",
+        &format!("[{}]", review_requested_pr(1, "Change", "testuser")),
+    );
+    let notifier = temp_dir.path().join("bin").join("notify-send");
+    fs::write(
+        &notifier,
+        "#!/bin/sh\nprintf '%s|' \"$@\" > \"$(dirname \"$0\")/notified\"\n",
+    )
+    .unwrap();
+    fs::set_permissions(&notifier, fs::Permissions::from_mode(0o755)).unwrap();
+
+    let mut cmd = Command::cargo_bin("rr").unwrap();
+    cmd.arg("--notify-summary")
+        .xdg_dirs(&config_dir)
+        .env("PATH", &path)
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("Found 1 PR(s)"));
+
+    let notified = fs::read_to_string(temp_dir.path().join("bin").join("notified")).unwrap();
+    assert!(notified.starts_with("Review Radar|You have 1 PR awaiting review"));
+}
+
+#[test]
+fn test_none_format_exit_code() {
+    let temp_dir = TempDir::new().unwrap();
+    let config_dir = create_test_config(&temp_dir, vec!["org1"], "testuser", None);
+    let path = install_fake_gh(&temp_dir);
+    fake_gh_fixture(&temp_dir, "repos/org1.json", r#"[{"name":"api"}]"#);
+    fake_gh_fixture(
+        &temp_dir,
+        "prs/org1_api.json",
+        &format!("[{}]", review_requested_pr(1, "Change", "testuser")),
+    );
+
+    let mut cmd = Command::cargo_bin("rr").unwrap();
+    cmd.args(["--format", "none"])
+        .xdg_dirs(&config_dir)
+        .env("PATH", &path)
+        .assert()
+        .success()
+        .stdout("")
+        .stderr("");
+
+    fake_gh_fixture(&temp_dir, "prs/org1_api.json", "[]");
+    let mut cmd = Command::cargo_bin("rr").unwrap();
+    cmd.args(["--format", "none"])
+        .xdg_dirs(&config_dir)
+        .env("PATH", &path)
+        .assert()
+        .code(1)
+        .stdout("")
+        .stderr("");
+}
+
+#[test]
+fn test_init_interactive() {
+    let temp_dir = TempDir::new().unwrap();
+    let config_dir = temp_dir.path().join("config");
+    let path = install_fake_gh(&temp_dir);
+    fake_gh_fixture(&temp_dir, "api/user_orgs", "org1\norg2\n");
+    fake_gh_fixture(&temp_dir, "api/user", "testuser\n");
+    fake_gh_fixture(
+        &temp_dir,
+        "repos/org2.json",
+        r#"[{"name":"api"},{"name":"web"},{"name":"api-docs"}]"#,
+    );
+
+    // A non-member org is declined, a bad pattern retried, the login defaulted
+    let mut cmd = Command::cargo_bin("rr").unwrap();
+    cmd.args(["init", "--interactive"])
+        .xdg_dirs(&config_dir)
+        .env("PATH", &path)
+        .write_stdin("other-org\nn\n2\n\napi(\n^api\ny\n")
+        .assert()
+        .success()
+        .stdout(predicate::str::contains(
+            "You're not a member of: other-org",
+        ))
+        .stdout(predicate::str::contains("Your GitHub username [testuser]"))
+        .stdout(predicate::str::contains("Invalid regex pattern 'api('"))
+        .stdout(predicate::str::contains(
+            "org2: 2 of 3 repositories would be scanned",
+        ))
+        .stdout(predicate::str::contains("Configuration saved successfully"));
+
+    let config =
+        Config::load_from_path(&config_dir.join("review-radar").join("config.toml")).unwrap();
+    assert_eq!(config.orgs, vec!["org2"]);
+    assert_eq!(config.username, "testuser");
+    assert_eq!(config.repo_pattern.as_deref(), Some("^api"));
+
+    // Without a terminal, a bare init asks for the arguments instead of prompting
+    let mut cmd = Command::cargo_bin("rr").unwrap();
+    cmd.arg("init")
+        .xdg_dirs(&config_dir)
+        .env("PATH", &path)
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("rr init --interactive"));
+}
+
+#[test]
+fn test_for_other_reviewers() {
+    let temp_dir = TempDir::new().unwrap();
+    let config_dir = create_test_config(&temp_dir, vec!["org1"], "testuser", None);
+    let path = install_fake_gh(&temp_dir);
+    fake_gh_fixture(&temp_dir, "repos/org1.json", r#"[{"name":"api"}]"#);
+    fake_gh_fixture(
+        &temp_dir,
+        "prs/org1_api.json",
+        &format!(
+            "[{},{},{}]",
+            review_requested_pr(1, "For bob", "bob"),
+            review_requested_pr(2, "For carol", "carol"),
+            review_requested_pr(3, "For me", "testuser")
+        ),
+    );
+
+    let mut cmd = Command::cargo_bin("rr").unwrap();
+    cmd.args(["--for", "bob", "--for", "carol"])
+        .xdg_dirs(&config_dir)
+        .env("PATH", &path)
+        .assert()
+        .success()
+        .stdout(predicate::str::contains(
+            "Found 2 PR(s) requesting review from bob, carol:",
+        ))
+        .stdout(predicate::str::contains(
+            "#1 - For bob\n   👤 Author: alice\n   🙋 Waiting for: bob",
+        ))
+        .stdout(predicate::str::contains("🙋 Waiting for: carol"))
+        .stdout(predicate::str::contains("For me").not());
+
+    let mut cmd = Command::cargo_bin("rr").unwrap();
+    cmd.args(["--for", "dave", "--format", "summary-only"])
+        .xdg_dirs(&config_dir)
+        .env("PATH", &path)
+        .assert()
+        .success()
+        .stdout("You have no PRs awaiting review from dave\n");
+}
+
+#[test]
+fn test_strict_fails_on_inaccessible_repo() {
+    let temp_dir = TempDir::new().unwrap();
+    let config_dir = create_test_config(&temp_dir, vec!["org1"], "testuser", None);
+    use_legacy_scan(&temp_dir);
+    let path = install_fake_gh(&temp_dir);
+    fake_gh_fixture(
+        &temp_dir,
+        "repos/org1.json",
+        r#"[{"name":"api"},{"name":"secret"}]"#,
+    );
+    fake_gh_fixture(&temp_dir, "prs/org1_api.json", "[]");
+
+    // Lenient by default: the repo without a fixture is skipped
+    let mut cmd = Command::cargo_bin("rr").unwrap();
+    cmd.xdg_dirs(&config_dir)
+        .env("PATH", &path)
+        .assert()
+        .success();
+
+    let mut cmd = Command::cargo_bin("rr").unwrap();
+    cmd.arg("--strict")
+        .xdg_dirs(&config_dir)
+        .env("PATH", &path)
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains(
+            "Failed to list the PRs of org1/secret (--strict)",
+        ));
+
+    fake_gh_fixture(&temp_dir, "api/users_org1", "");
+    fake_gh_fixture(&temp_dir, "api/users_gone", "");
+    let mut cmd = Command::cargo_bin("rr").unwrap();
+    cmd.args(["--strict", "--orgs", "org1,gone"])
+        .xdg_dirs(&config_dir)
+        .env("PATH", &path)
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains(
+            "Failed to list repositories for gone (--strict)",
+        ));
+}
+
+#[test]
+fn test_json_errors() {
+    let temp_dir = TempDir::new().unwrap();
+    let config_dir = create_test_config(&temp_dir, vec!["org1"], "testuser", None);
+
+    let mut cmd = Command::cargo_bin("rr").unwrap();
+    let output = cmd
+        .args(["--format", "json", "--title-pattern", "fix:("])
+        .xdg_dirs(&config_dir)
+        .output()
+        .unwrap();
+    assert_eq!(output.status.code(), Some(1));
+    let error: serde_json::Value = serde_json::from_slice(&output.stderr).unwrap();
+    assert!(error["error"]
+        .as_str()
+        .unwrap()
+        .starts_with("Invalid regex pattern 'fix:('"));
+
+    // The config's default format counts too
+    fs::write(
+        std::path::Path::new(&config_dir)
+            .join("review-radar")
+            .join("config.toml"),
+        "orgs = [\"org1\"]\nusername = \"testuser\"\n\n[defaults]\nformat = \"json\"\n",
+    )
+    .unwrap();
+    let mut cmd = Command::cargo_bin("rr").unwrap();
+    cmd.args(["--title-pattern", "fix:("])
+        .xdg_dirs(&config_dir)
+        .assert()
+        .failure()
+        .stderr(predicate::str::starts_with("{\"error\":"));
+
+    // Other formats keep the usual message
+    let mut cmd = Command::cargo_bin("rr").unwrap();
+    cmd.args(["--format", "text", "--title-pattern", "fix:("])
+        .xdg_dirs(&config_dir)
+        .assert()
+        .failure()
+        .stderr(predicate::str::starts_with("Error: Invalid regex pattern"));
+}
+
+#[test]
+fn test_repos_active_since() {
+    let temp_dir = TempDir::new().unwrap();
+    let config_dir = create_test_config(&temp_dir, vec!["org1"], "testuser", None);
+    let path = install_fake_gh(&temp_dir);
+    fake_gh_fixture(
+        &temp_dir,
+        "repos/org1.json",
+        r#"[{"name":"busy","pushedAt":"2024-03-02T09:00:00Z"},
+           {"name":"stale","pushedAt":"2023-06-01T09:00:00Z"},
+           {"name":"unknown"}]"#,
+    );
+
+    let mut cmd = Command::cargo_bin("rr").unwrap();
+    cmd.args(["--repos-active-since", "2024-03-01"])
+        .xdg_dirs(&config_dir)
+        .env("PATH", &path)
+        .assert()
+        .success()
+        .stdout(predicate::str::contains(
+            "Skipping 1 of 3 repositories with no pushes since 2024-03-01",
+        ));
+    let calls = fake_gh_calls(&temp_dir);
+    assert!(
+        calls.contains("--json name,visibility,pushedAt,viewerPermission"),
+        "{}",
+        calls
+    );
+    assert!(calls.contains("--repo org1/busy"), "{}", calls);
+    assert!(calls.contains("--repo org1/unknown"), "{}", calls);
+    assert!(!calls.contains("--repo org1/stale"), "{}", calls);
+
+    let mut cmd = Command::cargo_bin("rr").unwrap();
+    cmd.args(["--repos-active-since", "last week"])
+        .xdg_dirs(&config_dir)
+        .env("PATH", &path)
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("'last week' is not a date"));
+}
+
+#[test]
+fn test_record_history_and_stats() {
+    let temp_dir = TempDir::new().unwrap();
+    let config_dir = create_test_config(&temp_dir, vec!["org1"], "testuser", None);
+    let path = install_fake_gh(&temp_dir);
+    fake_gh_fixture(&temp_dir, "repos/org1.json", r#"[{"name":"api"}]"#);
+
+    let mut cmd = Command::cargo_bin("rr").unwrap();
+    cmd.arg("stats")
+        .xdg_dirs(&config_dir)
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("No history recorded yet"));
+
+    for prs in [
+        format!(
+            "[{},{}]",
+            review_requested_pr(1, "First", "testuser"),
+            review_requested_pr(2, "Second", "testuser")
+        ),
+        format!("[{}]", review_requested_pr(2, "Second", "testuser")),
+    ] {
+        fake_gh_fixture(&temp_dir, "prs/org1_api.json", &prs);
+        let mut cmd = Command::cargo_bin("rr").unwrap();
+        cmd.arg("--record-history")
+            .xdg_dirs(&config_dir)
+            .env("PATH", &path)
+            .assert()
+            .success();
+    }
+
+    let history = fs::read_to_string(
+        std::path::Path::new(&config_dir)
+            .join("review-radar")
+            .join("history.jsonl"),
+    )
+    .unwrap();
+    assert_eq!(history.lines().count(), 2);
+
+    let mut cmd = Command::cargo_bin("rr").unwrap();
+    cmd.arg("stats")
+        .xdg_dirs(&config_dir)
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("2 recorded scan(s)"))
+        .stdout(predicate::str::contains("Current backlog: 1 PR(s)"))
+        .stdout(predicate::str::contains("Reviewed: 1 PR(s)"));
+
+    let mut cmd = Command::cargo_bin("rr").unwrap();
+    cmd.args(["--record-history", "--own"])
+        .xdg_dirs(&config_dir)
+        .env("PATH", &path)
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("the search mode is 'own'"));
+}
+
+#[test]
+fn test_state_and_cache_dirs() {
+    let temp_dir = TempDir::new().unwrap();
+    let config_dir = create_test_config(&temp_dir, vec!["org1"], "testuser", None);
+    use_legacy_scan(&temp_dir);
+    let path = install_fake_gh(&temp_dir);
+    fake_gh_fixture(&temp_dir, "repos/org1.json", r#"[{"name":"api"}]"#);
+    fake_gh_fixture(&temp_dir, "prs/org1_api.json", "[]");
+    let state_dir = temp_dir.path().join("state");
+    let cache_dir = temp_dir.path().join("cache");
+
+    let mut cmd = Command::cargo_bin("rr").unwrap();
+    cmd.arg("--record-history")
+        .env("XDG_CONFIG_HOME", &config_dir)
+        .env("XDG_STATE_HOME", &state_dir)
+        .env("XDG_CACHE_HOME", &cache_dir)
+        .env("PATH", &path)
+        .assert()
+        .success();
+
+    let files = |dir: &std::path::Path| -> Vec<String> {
+        let mut names: Vec<String> = fs::read_dir(dir.join("review-radar"))
+            .unwrap()
+            .map(|entry| entry.unwrap().file_name().to_string_lossy().to_string())
+            .collect();
+        names.sort();
+        names
+    };
+    assert_eq!(files(std::path::Path::new(&config_dir)), ["config.toml"]);
+    assert_eq!(files(&state_dir), ["history.jsonl"]);
+    assert_eq!(files(&cache_dir), ["repo-cache.json"]);
+}
+
+#[test]
+fn test_inbox_mode() {
+    let temp_dir = TempDir::new().unwrap();
+    let config_dir = create_test_config(&temp_dir, vec!["org1"], "testuser", None);
+    let path = install_fake_gh(&temp_dir);
+    fake_gh_fixture(&temp_dir, "repos/org1.json", r#"[{"name":"api"}]"#);
+    let pr = |number: u32, title: &str, author: &str, extra: &str| {
+        format!(
+            r#"{{"number":{0},"title":"{1}","url":"https://github.com/pull/{0}","author":{{"login":"{2}"}},"reviewRequests":[],"assignees":[]{3}}}"#,
+            number, title, author, extra
+        )
+    };
+    fake_gh_fixture(
+        &temp_dir,
+        "prs/org1_api.json",
+        &format!(
+            "[{},{},{},{},{}]",
+            pr(1, "Mentioned", "alice", r#","body":"cc @TestUser""#),
+            pr(
+                2,
+                "Assigned",
+                "alice",
+                r#","assignees":[{"login":"testuser"}]"#
+            ),
+            pr(
+                3,
+                "Requested",
+                "alice",
+                r#","reviewRequests":[{"login":"testuser"}]"#
+            ),
+            pr(
+                4,
+                "Mine",
+                "testuser",
+                r#","assignees":[{"login":"testuser"}]"#
+            ),
+            pr(5, "Other", "alice", r#","body":"cc @testuser-bot""#),
+        ),
+    );
+
+    let mut cmd = Command::cargo_bin("rr").unwrap();
+    let output = cmd
+        .args(["--mode", "inbox", "--format", "json", "--no-metadata"])
+        .xdg_dirs(&config_dir)
+        .env("PATH", &path)
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+    let prs: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+    let found: Vec<(u64, String)> = prs
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|pr| {
+            (
+                pr["number"].as_u64().unwrap(),
+                pr["reasons"][0].as_str().unwrap().to_string(),
+            )
+        })
+        .collect();
+    assert_eq!(
+        found,
+        [
+            (3, "review".to_string()),
+            (2, "assignee".to_string()),
+            (1, "mention".to_string())
+        ]
+    );
+    assert!(fake_gh_calls(&temp_dir).contains("assignees,body"));
+
+    let mut cmd = Command::cargo_bin("rr").unwrap();
+    cmd.args(["--mode", "inbox", "--team-only"])
+        .xdg_dirs(&config_dir)
+        .env("PATH", &path)
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("the search mode is 'inbox'"));
+}
+
+#[test]
+fn test_title_width() {
+    let temp_dir = TempDir::new().unwrap();
+    let config_dir = create_test_config(&temp_dir, vec!["org1"], "testuser", None);
+    let path = install_fake_gh(&temp_dir);
+    fake_gh_fixture(&temp_dir, "repos/org1.json", r#"[{"name":"api"}]"#);
+    fake_gh_fixture(
+        &temp_dir,
+        "prs/org1_api.json",
+        &format!(
+            "[{}]",
+            review_requested_pr(1, "Refactor the session handling", "testuser")
+        ),
+    );
+
+    // Not a terminal, so nothing is cut unless asked
+    let mut cmd = Command::cargo_bin("rr").unwrap();
+    cmd.xdg_dirs(&config_dir)
+        .env("PATH", &path)
+        .assert()
+        .success()
+        .stdout(predicate::str::contains(
+            "#1 - Refactor the session handling",
+        ));
+
+    let mut cmd = Command::cargo_bin("rr").unwrap();
+    cmd.args(["--title-width", "12"])
+        .xdg_dirs(&config_dir)
+        .env("PATH", &path)
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("#1 - Refactor th…\n"));
+}
+
+#[test]
+fn test_explain() {
+    let temp_dir = TempDir::new().unwrap();
+    let config_dir = create_test_config(&temp_dir, vec!["org1"], "testuser", Some("^api"));
+    let path = install_fake_gh(&temp_dir);
+    fake_gh_fixture(
+        &temp_dir,
+        "repos/org1.json",
+        r#"[{"name":"api","visibility":"PRIVATE"}]"#,
+    );
+    fake_gh_fixture(
+        &temp_dir,
+        "prs/org1_api.json",
+        &format!("[{}]", review_requested_pr(1, "fix: login", "testuser")),
+    );
+
+    let mut cmd = Command::cargo_bin("rr").unwrap();
+    cmd.args([
+        "--explain",
+        "--visibility",
+        "private",
+        "--title-pattern",
+        "^fix",
+    ])
+    .xdg_dirs(&config_dir)
+    .env("PATH", &path)
+    .assert()
+    .success()
+    .stdout(predicate::str::contains(
+        "💡 Shown because: review requested from testuser; repo matched pattern '^api'; repo is private; title matched '^fix'",
+    ));
+
+    let mut cmd = Command::cargo_bin("rr").unwrap();
+    cmd.xdg_dirs(&config_dir)
+        .env("PATH", &path)
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("Shown because").not());
+}
+
+#[test]
+fn test_sort_by_repo() {
+    let temp_dir = TempDir::new().unwrap();
+    let config_dir = create_test_config(&temp_dir, vec!["org1"], "testuser", None);
+    let path = install_fake_gh(&temp_dir);
+    fake_gh_fixture(
+        &temp_dir,
+        "repos/org1.json",
+        r#"[{"name":"web"},{"name":"api"}]"#,
+    );
+    fake_gh_fixture(
+        &temp_dir,
+        "prs/org1_web.json",
+        &format!("[{}]", review_requested_pr(1, "Web", "testuser")),
+    );
+    fake_gh_fixture(
+        &temp_dir,
+        "prs/org1_api.json",
+        &format!(
+            "[{},{}]",
+            review_requested_pr(9, "Api later", "testuser"),
+            review_requested_pr(3, "Api first", "testuser")
+        ),
+    );
+
+    let mut cmd = Command::cargo_bin("rr").unwrap();
+    cmd.args(["--sort", "repo", "--format", "urls"])
+        .xdg_dirs(&config_dir)
+        .env("PATH", &path)
+        .assert()
+        .success()
+        .stdout(
+            "https://github.com/pull/3\nhttps://github.com/pull/9\nhttps://github.com/pull/1\n",
+        );
+}
+
+#[test]
+fn test_cache_warm() {
+    let temp_dir = TempDir::new().unwrap();
+    let config_dir = create_test_config(&temp_dir, vec!["org1"], "testuser", None);
+    let path = install_fake_gh(&temp_dir);
+    fake_gh_fixture(&temp_dir, "repos/org1.json", r#"[{"name":"api"}]"#);
+    fake_gh_fixture(&temp_dir, "prs/org1_api.json", "[]");
+
+    let mut cmd = Command::cargo_bin("rr").unwrap();
+    cmd.args(["cache", "warm"])
+        .xdg_dirs(&config_dir)
+        .env("PATH", &path)
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("🔥 Cached 1 repositories"));
+    let calls = fake_gh_calls(&temp_dir);
+    assert!(calls.contains("repo list org1"));
+    assert!(!calls.contains("pr list"));
+
+    // The scan uses the warmed lists instead of listing again
+    let mut cmd = Command::cargo_bin("rr").unwrap();
+    cmd.xdg_dirs(&config_dir)
+        .env("PATH", &path)
+        .assert()
+        .success();
+    assert_eq!(
+        fake_gh_calls(&temp_dir).matches("repo list org1").count(),
+        1
+    );
+
+    fs::remove_file(temp_dir.path().join("bin").join("repos/org1.json")).unwrap();
+    let mut cmd = Command::cargo_bin("rr").unwrap();
+    cmd.args(["cache", "warm"])
+        .xdg_dirs(&config_dir)
+        .env("PATH", &path)
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains(
+            "Couldn't list the repositories of org1",
+        ));
+}
+
+#[test]
+fn test_warns_about_login_that_never_matched() {
+    let temp_dir = TempDir::new().unwrap();
+    let config_dir = create_test_config(&temp_dir, vec!["org1"], "tsetuser", None);
+    use_legacy_scan(&temp_dir);
+    let path = install_fake_gh(&temp_dir);
+    let names: Vec<String> = (0..20)
+        .map(|i| format!(r#"{{"name":"repo{}"}}"#, i))
+        .collect();
+    fake_gh_fixture(
+        &temp_dir,
+        "repos/org1.json",
+        &format!("[{}]", names.join(",")),
+    );
+    for i in 0..20 {
+        fake_gh_fixture(&temp_dir, &format!("prs/org1_repo{}.json", i), "[]");
+    }
+
+    let mut cmd = Command::cargo_bin("rr").unwrap();
+    cmd.xdg_dirs(&config_dir)
+        .env("PATH", &path)
+        .assert()
+        .success()
+        .stdout(predicate::str::contains(
+            "No review has ever been requested from 'tsetuser' in the 20 repositories scanned",
+        ));
+
+    // Once a login has matched, coming up empty is nothing unusual
+    fake_gh_fixture(
+        &temp_dir,
+        "prs/org1_repo0.json",
+        &format!("[{}]", review_requested_pr(1, "First", "tsetuser")),
+    );
+    let mut cmd = Command::cargo_bin("rr").unwrap();
+    cmd.xdg_dirs(&config_dir)
+        .env("PATH", &path)
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("No review has ever been requested").not());
+    fake_gh_fixture(&temp_dir, "prs/org1_repo0.json", "[]");
+    let mut cmd = Command::cargo_bin("rr").unwrap();
+    cmd.xdg_dirs(&config_dir)
+        .env("PATH", &path)
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("No review has ever been requested").not());
+}
+
+#[test]
+fn test_search_warns_about_login_that_never_matched() {
+    let temp_dir = TempDir::new().unwrap();
+    let config_dir = create_test_config(&temp_dir, vec!["org1"], "tsetuser", None);
+    let path = install_fake_gh(&temp_dir);
+    fake_gh_fixture(&temp_dir, "prs/org1_api.json", "[]");
+
+    let mut cmd = Command::cargo_bin("rr").unwrap();
+    cmd.xdg_dirs(&config_dir)
+        .env("PATH", &path)
+        .assert()
+        .success()
+        .stdout(predicate::str::contains(
+            "No review has ever been requested from 'tsetuser' in any PR in org1",
+        ));
+    assert!(fake_gh_calls(&temp_dir).contains(
+        "search prs --json number --limit 1 -- is:pr user-review-requested:tsetuser org:org1"
+    ));
+
+    // A finished review no longer counts as a request, but shows the login is right
+    fake_gh_fixture(
+        &temp_dir,
+        "prs/org1_api.json",
+        r#"[{"number":1,"title":"Reviewed","url":"u1","author":{"login":"alice"},"reviewRequests":[],"reviews":[{"author":{"login":"tsetuser"}}]}]"#,
+    );
+    let mut cmd = Command::cargo_bin("rr").unwrap();
+    cmd.xdg_dirs(&config_dir)
+        .env("PATH", &path)
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("No review has ever been requested").not());
+    assert!(fake_gh_calls(&temp_dir)
+        .contains("search prs --json number --limit 1 -- is:pr reviewed-by:tsetuser org:org1"));
+    let matched_logins = temp_dir
+        .path()
+        .join("config/review-radar/matched-logins.json");
+    fs::remove_file(&matched_logins).unwrap();
+
+    // A request on a closed PR shows the login is right, and is remembered
+    fake_gh_fixture(
+        &temp_dir,
+        "prs/org1_api.json",
+        &format!(
+            "[{}]",
+            review_requested_pr(1, "Merged", "tsetuser").replacen('{', r#"{"state":"MERGED","#, 1)
+        ),
+    );
+    let mut cmd = Command::cargo_bin("rr").unwrap();
+    cmd.xdg_dirs(&config_dir)
+        .env("PATH", &path)
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("No review has ever been requested").not());
+    fake_gh_fixture(&temp_dir, "prs/org1_api.json", "[]");
+    fs::remove_file(temp_dir.path().join("bin/calls.log")).unwrap();
+    let mut cmd = Command::cargo_bin("rr").unwrap();
+    cmd.xdg_dirs(&config_dir)
+        .env("PATH", &path)
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("No review has ever been requested").not());
+    assert!(!fake_gh_calls(&temp_dir).contains("--limit 1 "));
+}
+
+#[test]
+fn test_url_base() {
+    let temp_dir = TempDir::new().unwrap();
+    let config_dir = create_test_config(&temp_dir, vec!["org1"], "testuser", None);
+    let path = install_fake_gh(&temp_dir);
+    fake_gh_fixture(&temp_dir, "repos/org1.json", r#"[{"name":"api"}]"#);
+    fake_gh_fixture(
+        &temp_dir,
+        "prs/org1_api.json",
+        &format!("[{}]", review_requested_pr(4, "First", "testuser")),
+    );
+
+    let mut cmd = Command::cargo_bin("rr").unwrap();
+    cmd.args(["--url-base", "/", "--format", "urls"])
+        .xdg_dirs(&config_dir)
+        .env("PATH", &path)
+        .assert()
+        .success()
+        .stdout("/pull/4\n");
+}
+
+#[test]
+fn test_org_prefix_expansion() {
+    let temp_dir = TempDir::new().unwrap();
+    let config_dir = create_test_config(&temp_dir, vec!["org1"], "testuser", None);
+    let path = install_fake_gh(&temp_dir);
+    fake_gh_fixture(&temp_dir, "api/users_acme-eng", "");
+    fake_gh_fixture(
+        &temp_dir,
+        "api/user_orgs",
+        "acme-eng\nacme-infra\nacmecorp\n",
+    );
+    fake_gh_fixture(&temp_dir, "repos/acme-eng.json", "[]");
+    fake_gh_fixture(&temp_dir, "repos/acme-infra.json", "[]");
+
+    let mut cmd = Command::cargo_bin("rr").unwrap();
+    cmd.args(["--orgs", "acme-"])
+        .xdg_dirs(&config_dir)
+        .env("PATH", &path)
+        .assert()
+        .success()
+        .stdout(predicate::str::contains(
+            "Expanded 'acme-' to acme-eng, acme-infra",
+        ));
+    let calls = fake_gh_calls(&temp_dir);
+    assert!(calls.contains("org:acme-infra"));
+    assert!(!calls.contains("org:acmecorp"));
+
+    // Names that exist are used as typed, without fetching memberships
+    fs::remove_file(temp_dir.path().join("bin").join("calls.log")).unwrap();
+    let mut cmd = Command::cargo_bin("rr").unwrap();
+    cmd.args(["--orgs", "acme-eng"])
+        .xdg_dirs(&config_dir)
+        .env("PATH", &path)
+        .assert()
+        .success();
+    assert!(!fake_gh_calls(&temp_dir).contains("user/orgs"));
+
+    let mut cmd = Command::cargo_bin("rr").unwrap();
+    cmd.args(["--orgs", "acme"])
+        .xdg_dirs(&config_dir)
+        .env("PATH", &path)
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains(
+            "could mean any of acme-eng, acme-infra, acmecorp",
+        ));
+}
+
+#[test]
+fn test_exclude_repos() {
+    let temp_dir = TempDir::new().unwrap();
+    let config_dir = create_test_config(&temp_dir, vec!["org1"], "testuser", None);
+    use_legacy_scan(&temp_dir);
+    let path = install_fake_gh(&temp_dir);
+    fake_gh_fixture(
+        &temp_dir,
+        "repos/org1.json",
+        r#"[{"name":"api"},{"name":"api-sandbox"},{"name":"web-test"}]"#,
+    );
+    fake_gh_fixture(&temp_dir, "prs/org1_api.json", "[]");
+
+    let mut cmd = Command::cargo_bin("rr").unwrap();
+    cmd.args(["set", "--exclude-repo", "org1/a/b"])
+        .xdg_dirs(&config_dir)
+        .assert()
+        .stdout(predicate::str::contains(
+            "Invalid repository glob 'org1/a/b'",
+        ));
+
+    let mut cmd = Command::cargo_bin("rr").unwrap();
+    cmd.args([
+        "set",
+        "--exclude-repo",
+        "*-sandbox",
+        "--exclude-repo",
+        "org1/*-test",
+    ])
+    .xdg_dirs(&config_dir)
+    .assert()
+    .success()
+    .stdout(predicate::str::contains("Updated excluded repositories"));
+
+    let mut cmd = Command::cargo_bin("rr").unwrap();
+    cmd.xdg_dirs(&config_dir)
+        .env("PATH", &path)
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("Excluding 2 of 3 repositories"));
+    let calls = fake_gh_calls(&temp_dir);
+    assert!(calls.contains("--repo org1/api "));
+    assert!(!calls.contains("org1/api-sandbox"));
+    assert!(!calls.contains("org1/web-test"));
+}
+
+#[test]
+fn test_min_permission() {
+    let temp_dir = TempDir::new().unwrap();
+    let config_dir = create_test_config(&temp_dir, vec!["org1"], "testuser", None);
+    let path = install_fake_gh(&temp_dir);
+    fake_gh_fixture(
+        &temp_dir,
+        "repos/org1.json",
+        r#"[{"name":"mine","viewerPermission":"ADMIN"},
+           {"name":"team","viewerPermission":"WRITE"},
+           {"name":"docs","viewerPermission":"READ"}]"#,
+    );
+    for repo in ["mine", "team", "docs"] {
+        fake_gh_fixture(&temp_dir, &format!("prs/org1_{}.json", repo), "[]");
+    }
+
+    let mut cmd = Command::cargo_bin("rr").unwrap();
+    cmd.args(["--min-permission", "write"])
+        .xdg_dirs(&config_dir)
+        .env("PATH", &path)
+        .assert()
+        .success()
+        .stdout(predicate::str::contains(
+            "Skipping 1 of 3 repositories where you have less than write access",
+        ));
+    let calls = fake_gh_calls(&temp_dir);
+    assert!(calls.contains("--repo org1/team "));
+    assert!(!calls.contains("--repo org1/docs "));
+}
+
+#[test]
+fn test_multiple_outputs() {
+    let temp_dir = TempDir::new().unwrap();
+    let config_dir = create_test_config(&temp_dir, vec!["org1"], "testuser", None);
+    let path = install_fake_gh(&temp_dir);
+    fake_gh_fixture(&temp_dir, "repos/org1.json", r#"[{"name":"api"}]"#);
+    fake_gh_fixture(
+        &temp_dir,
+        "prs/org1_api.json",
+        &format!("[{}]", review_requested_pr(1, "Fix", "testuser")),
+    );
+    let json_path = temp_dir.path().join("report.json");
+    let text_path = temp_dir.path().join("report.txt");
+
+    let mut cmd = Command::cargo_bin("rr").unwrap();
+    cmd.args(["--format", "urls", "--no-metadata"])
+        .arg("--output")
+        .arg(format!("{}:json", json_path.display()))
+        .arg("--output")
+        .arg(format!("{}:text", text_path.display()))
+        .xdg_dirs(&config_dir)
+        .env("PATH", &path)
+        .assert()
+        .success()
+        .stdout("https://github.com/pull/1\n");
+
+    let json: serde_json::Value =
+        serde_json::from_str(&fs::read_to_string(&json_path).unwrap()).unwrap();
+    assert_eq!(json[0]["number"], 1);
+    let text = fs::read_to_string(&text_path).unwrap();
+    assert!(text.contains("🔗 #1 - Fix"), "{}", text);
+    assert_eq!(fake_gh_calls(&temp_dir).matches("search prs").count(), 1);
+
+    let mut cmd = Command::cargo_bin("rr").unwrap();
+    cmd.args(["--output", "report.yaml:yaml"])
+        .xdg_dirs(&config_dir)
+        .env("PATH", &path)
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("'yaml' is not an output format"));
+}
+
+#[test]
+fn test_highlight_oldest() {
+    let temp_dir = TempDir::new().unwrap();
+    let config_dir = create_test_config(&temp_dir, vec!["org1"], "testuser", None);
+    let path = install_fake_gh(&temp_dir);
+    fake_gh_fixture(&temp_dir, "repos/org1.json", r#"[{"name":"api"}]"#);
+    let opened = |number: u32, title: &str, created_at: &str| {
+        review_requested_pr(number, title, "testuser").replace(
+            r#""author""#,
+            &format!(r#""createdAt":"{}","author""#, created_at),
+        )
+    };
+    fake_gh_fixture(
+        &temp_dir,
+        "prs/org1_api.json",
+        &format!(
+            "[{},{}]",
+            opened(1, "Recent", "2099-01-01T00:00:00Z"),
+            opened(2, "Ancient", "2020-01-01T00:00:00Z")
+        ),
+    );
+
+    let mut cmd = Command::cargo_bin("rr").unwrap();
+    cmd.args(["--highlight-oldest", "1"])
+        .xdg_dirs(&config_dir)
+        .env("PATH", &path)
+        .assert()
+        .success()
+        .stdout(predicate::str::contains(
+            "🔥 Most overdue:\n   #2 Ancient (org1/api), waiting",
+        ))
+        .stdout(predicate::str::contains("Recent (org1/api)").not());
+
+    // Two PRs fit under the default of three, so there's nothing to highlight
+    let mut cmd = Command::cargo_bin("rr").unwrap();
+    cmd.xdg_dirs(&config_dir)
+        .env("PATH", &path)
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("Most overdue").not());
+}
+
+#[test]
+fn test_invalid_utf8_from_gh() {
+    let temp_dir = TempDir::new().unwrap();
+    let config_dir = create_test_config(&temp_dir, vec!["org1"], "testuser", None);
+    use_legacy_scan(&temp_dir);
+    let path = install_fake_gh(&temp_dir);
+    let bin_dir = temp_dir.path().join("bin");
+    fs::write(
+        bin_dir.join("repos/org1.json"),
+        b"[{\"name\":\"api\",\"description\":\"caf\xe9\"}]",
+    )
+    .unwrap();
+    let pr = review_requested_pr(1, "Fix TITLE", "testuser");
+    let (before, after) = pr.split_once("TITLE").unwrap();
+    fs::write(
+        bin_dir.join("prs/org1_api.json"),
+        [b"[", before.as_bytes(), b"\xff", after.as_bytes(), b"]"].concat(),
+    )
+    .unwrap();
+
+    for extra in [None, Some("--no-jq")] {
+        let mut cmd = Command::cargo_bin("rr").unwrap();
+        cmd.args(["--format", "plain"])
+            .args(extra)
+            .xdg_dirs(&config_dir)
+            .env("PATH", &path)
+            .assert()
+            .success()
+            .stdout(predicate::str::contains("#1 Fix \u{fffd}"));
+    }
+}
+
+#[test]
+fn test_hyperlinks_ignored_when_piped() {
+    let temp_dir = TempDir::new().unwrap();
+    let config_dir = create_test_config(&temp_dir, vec!["org1"], "testuser", None);
+    let path = install_fake_gh(&temp_dir);
+    fake_gh_fixture(&temp_dir, "repos/org1.json", r#"[{"name":"api"}]"#);
+    fake_gh_fixture(
+        &temp_dir,
+        "prs/org1_api.json",
+        &format!("[{}]", review_requested_pr(1, "Fix", "testuser")),
+    );
+
+    let mut cmd = Command::cargo_bin("rr").unwrap();
+    cmd.arg("--hyperlinks")
+        .xdg_dirs(&config_dir)
+        .env("PATH", &path)
+        .assert()
+        .success()
+        .stdout(predicate::str::contains(
+            "🌐 URL: https://github.com/pull/1",
+        ))
+        .stdout(predicate::str::contains("\x1b]8;;").not());
+}
+
+#[test]
+fn test_retry_failed() {
+    let temp_dir = TempDir::new().unwrap();
+    let config_dir = create_test_config(&temp_dir, vec!["org1"], "testuser", None);
+    use_legacy_scan(&temp_dir);
+    let path = install_fake_gh(&temp_dir);
+    fake_gh_fixture(
+        &temp_dir,
+        "repos/org1.json",
+        r#"[{"name":"api"},{"name":"web"}]"#,
+    );
+    fake_gh_fixture(
+        &temp_dir,
+        "prs/org1_api.json",
+        &format!("[{}]", review_requested_pr(1, "Fix", "testuser")),
+    );
+
+    // Nothing failed yet
+    let mut cmd = Command::cargo_bin("rr").unwrap();
+    cmd.arg("retry-failed")
+        .xdg_dirs(&config_dir)
+        .env("PATH", &path)
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("nothing to retry"));
+
+    // org1/web has no fixture, so listing its PRs fails
+    let mut cmd = Command::cargo_bin("rr").unwrap();
+    cmd.args(["--format", "urls"])
+        .xdg_dirs(&config_dir)
+        .env("PATH", &path)
+        .assert()
+        .success()
+        .stdout("https://github.com/pull/1\n");
+
+    fake_gh_fixture(
+        &temp_dir,
+        "prs/org1_web.json",
+        &format!(
+            "[{}]",
+            review_requested_pr(2, "Docs", "testuser").replace("/pull/2", "/web/pull/2")
+        ),
+    );
+    fs::write(temp_dir.path().join("bin/calls.log"), "").unwrap();
+    let mut cmd = Command::cargo_bin("rr").unwrap();
+    cmd.args(["--format", "urls", "retry-failed"])
+        .xdg_dirs(&config_dir)
+        .env("PATH", &path)
+        .assert()
+        .success()
+        .stdout("https://github.com/pull/1\nhttps://github.com/web/pull/2\n")
+        .stderr(predicate::str::contains(
+            "Retrying the 1 repositories the last scan couldn't check",
+        ));
+    let calls = fake_gh_calls(&temp_dir);
+    assert!(calls.contains("--repo org1/web"), "{}", calls);
+    assert!(!calls.contains("--repo org1/api"), "{}", calls);
+    assert!(!calls.contains("repo list"), "{}", calls);
+
+    // Everything was checked, so there is nothing left to retry
+    let mut cmd = Command::cargo_bin("rr").unwrap();
+    cmd.arg("retry-failed")
+        .xdg_dirs(&config_dir)
+        .env("PATH", &path)
+        .assert()
+        .failure();
+}
+
+#[test]
+fn test_progress_by_org() {
+    let temp_dir = TempDir::new().unwrap();
+    let config_dir = create_test_config(&temp_dir, vec!["org1", "org2"], "testuser", None);
+    use_legacy_scan(&temp_dir);
+    let path = install_fake_gh(&temp_dir);
+    fake_gh_fixture(
+        &temp_dir,
+        "repos/org1.json",
+        r#"[{"name":"api"},{"name":"web"}]"#,
+    );
+    fake_gh_fixture(&temp_dir, "repos/org2.json", r#"[{"name":"docs"}]"#);
+    fake_gh_fixture(
+        &temp_dir,
+        "prs/org1_api.json",
+        &format!("[{}]", review_requested_pr(1, "Fix", "testuser")),
+    );
+    fake_gh_fixture(&temp_dir, "prs/org1_web.json", "[]");
+    fake_gh_fixture(&temp_dir, "prs/org2_docs.json", "[]");
+
+    let mut cmd = Command::cargo_bin("rr").unwrap();
+    cmd.args(["--progress", "orgs", "--format", "urls"])
+        .xdg_dirs(&config_dir)
+        .env("PATH", &path)
+        .assert()
+        .success()
+        .stderr(predicate::str::contains(
+            "✅ org1: 2 repos, 1 review request\n",
+        ))
+        .stderr(predicate::str::contains(
+            "✅ org2: 1 repo, 0 review requests\n",
+        ))
+        .stderr(predicate::str::contains("Checking repositories").not());
+
+    // Two orgs are few enough for the repository counter
+    let mut cmd = Command::cargo_bin("rr").unwrap();
+    cmd.args(["--format", "urls"])
+        .xdg_dirs(&config_dir)
+        .env("PATH", &path)
+        .assert()
+        .success()
+        .stderr(predicate::str::contains("Checking repositories"))
+        .stderr(predicate::str::contains("✅ org1").not());
+}
+
+#[test]
+fn test_project_filter() {
+    let temp_dir = TempDir::new().unwrap();
+    let config_dir = create_test_config(&temp_dir, vec!["org1"], "testuser", None);
+    let path = install_fake_gh(&temp_dir);
+    fake_gh_fixture(&temp_dir, "repos/org1.json", r#"[{"name":"api"}]"#);
+    fake_gh_fixture(
+        &temp_dir,
+        "prs/org1_api.json",
+        &format!(
+            "[{},{}]",
+            review_requested_pr(1, "On the board", "testuser"),
+            review_requested_pr(2, "Elsewhere", "testuser")
+        ),
+    );
+    fake_gh_fixture(
+        &temp_dir,
+        "projects/5.json",
+        r#"{"items":[
+            {"content":{"type":"PullRequest","url":"https://github.com/pull/1"}},
+            {"content":{"type":"Issue","url":"https://github.com/pull/2"}},
+            {"content":{"type":"DraftIssue","title":"Idea"}}
+        ]}"#,
+    );
+
+    let mut cmd = Command::cargo_bin("rr").unwrap();
+    cmd.args(["--project", "5", "--format", "urls"])
+        .xdg_dirs(&config_dir)
+        .env("PATH", &path)
+        .assert()
+        .success()
+        .stdout("https://github.com/pull/1\n")
+        .stderr(predicate::str::contains(
+            "1 of 2 PRs are on project 5 of org1",
+        ));
+    assert!(fake_gh_calls(&temp_dir).contains("project item-list 5 --owner org1"));
+
+    let mut cmd = Command::cargo_bin("rr").unwrap();
+    cmd.args(["--project", "6"])
+        .xdg_dirs(&config_dir)
+        .env("PATH", &path)
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("read:project"));
+}
+
+#[test]
+fn test_title_patterns() {
+    let temp_dir = TempDir::new().unwrap();
+    let config_dir = create_test_config(&temp_dir, vec!["org1"], "testuser", None);
+    let path = install_fake_gh(&temp_dir);
+    fake_gh_fixture(&temp_dir, "repos/org1.json", r#"[{"name":"api"}]"#);
+    fake_gh_fixture(
+        &temp_dir,
+        "prs/org1_api.json",
+        &format!(
+            "[{},{},{}]",
+            review_requested_pr(1, "fix: crash on start", "testuser"),
+            review_requested_pr(2, "fix: WIP retry logic", "testuser"),
+            review_requested_pr(3, "feat: dark mode", "testuser")
+        ),
+    );
+
+    let mut cmd = Command::cargo_bin("rr").unwrap();
+    cmd.args(["--title-pattern", "^fix:", "--exclude-title-pattern", "WIP"])
+        .args(["--format", "urls"])
+        .xdg_dirs(&config_dir)
+        .env("PATH", &path)
+        .assert()
+        .success()
+        .stdout("https://github.com/pull/1\n");
+
+    let calls_before = fake_gh_calls(&temp_dir);
+    let mut cmd = Command::cargo_bin("rr").unwrap();
+    cmd.args(["--title-pattern", "fix:("])
+        .xdg_dirs(&config_dir)
+        .env("PATH", &path)
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("Invalid regex pattern 'fix:('"));
+    // The bad pattern is caught before anything is scanned
+    assert_eq!(fake_gh_calls(&temp_dir), calls_before);
+}
+
+#[test]
+fn test_status_file() {
+    let temp_dir = TempDir::new().unwrap();
+    let config_dir = create_test_config(&temp_dir, vec!["org1"], "testuser", None);
+    let path = install_fake_gh(&temp_dir);
+    fake_gh_fixture(&temp_dir, "repos/org1.json", r#"[{"name":"api"}]"#);
+    fake_gh_fixture(
+        &temp_dir,
+        "prs/org1_api.json",
+        &format!("[{}]", review_requested_pr(1, "Change", "testuser")),
+    );
+    let status_dir = temp_dir.path().join("status");
+    fs::create_dir_all(&status_dir).unwrap();
+    let status_file = status_dir.join("rr-count");
+
+    let mut cmd = Command::cargo_bin("rr").unwrap();
+    cmd.arg("--status-file")
+        .arg(&status_file)
+        .xdg_dirs(&config_dir)
+        .env("PATH", &path)
+        .assert()
+        .success();
+    assert_eq!(fs::read_to_string(&status_file).unwrap(), "1\n");
+
+    fake_gh_fixture(&temp_dir, "prs/org1_api.json", "[]");
+    let mut cmd = Command::cargo_bin("rr").unwrap();
+    cmd.arg("--status-file")
+        .arg(&status_file)
+        .xdg_dirs(&config_dir)
+        .env("PATH", &path)
+        .assert()
+        .success();
+    assert_eq!(fs::read_to_string(&status_file).unwrap(), "0\n");
+    assert_eq!(fs::read_dir(&status_dir).unwrap().count(), 1);
+}
+
+#[test]
+fn test_discord_webhook() {
+    use std::os::unix::fs::PermissionsExt;
+
+    let temp_dir = TempDir::new().unwrap();
+    let config_dir = create_test_config(&temp_dir, vec!["org1"], "testuser", None);
+    let path = install_fake_gh(&temp_dir);
+    fake_gh_fixture(&temp_dir, "repos/org1.json", r#"[{"name":"api"}]"#);
+    fake_gh_fixture(
+        &temp_dir,
+        "prs/org1_api.json",
+        &format!("[{}]", review_requested_pr(1, "Change", "testuser")),
+    );
+    let curl = temp_dir.path().join("bin").join("curl");
+    fs::write(
+        &curl,
+        "#!/bin/sh\ndir=\"$(dirname \"$0\")\"\nfor last; do :; done\necho \"$last\" > \"$dir/posted_to\"\ncat > \"$dir/posted\"\n",
+    )
+    .unwrap();
+    fs::set_permissions(&curl, fs::Permissions::from_mode(0o755)).unwrap();
+
+    let mut cmd = Command::cargo_bin("rr").unwrap();
+    cmd.args(["--discord-webhook", "https://discord.example/hook"])
+        .xdg_dirs(&config_dir)
+        .env("PATH", &path)
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("Found 1 PR(s)"));
+
+    let bin = temp_dir.path().join("bin");
+    let posted_to = fs::read_to_string(bin.join("posted_to")).unwrap();
+    assert_eq!(posted_to.trim(), "https://discord.example/hook");
+    let posted: serde_json::Value =
+        serde_json::from_str(&fs::read_to_string(bin.join("posted")).unwrap()).unwrap();
+    assert_eq!(posted["content"], "You have 1 PR awaiting review");
+    assert_eq!(posted["embeds"][0]["title"], "org1/api#1 Change");
+}
+
+#[test]
+fn test_unparsable_pr_list_is_reported() {
+    let temp_dir = TempDir::new().unwrap();
+    let config_dir = create_test_config(&temp_dir, vec!["org1"], "testuser", None);
+    let path = install_fake_gh(&temp_dir);
+    fake_gh_fixture(
+        &temp_dir,
+        "repos/org1.json",
+        r#"[{"name":"api"},{"name":"web"}]"#,
+    );
+    fake_gh_fixture(&temp_dir, "prs/org1_api.json", "[]");
+    fake_gh_fixture(&temp_dir, "prs/org1_web.json", r#"[{"number":1,"title""#);
+
+    let mut cmd = Command::cargo_bin("rr").unwrap();
+    cmd.args(["--no-jq"])
+        .xdg_dirs(&config_dir)
+        .env("PATH", &path)
+        .assert()
+        .success()
+        .stdout(predicate::str::contains(
+            "Couldn't parse the PR list of 1 repositories",
+        ));
+
+    let mut cmd = Command::cargo_bin("rr").unwrap();
+    cmd.args(["--no-jq", "--verbose"])
+        .xdg_dirs(&config_dir)
+        .env("PATH", &path)
+        .assert()
+        .success()
+        .stdout(predicate::str::contains(
+            "Couldn't parse the PR list of org1/web, so its PRs are missing: EOF",
+        ));
+}
+
+#[test]
+fn test_json_no_metadata() {
+    let temp_dir = TempDir::new().unwrap();
+    let config_dir = create_test_config(&temp_dir, vec!["org1"], "testuser", Some("a.*"));
+    let path = install_fake_gh(&temp_dir);
+    fake_gh_fixture(&temp_dir, "repos/org1.json", r#"[{"name":"api"}]"#);
+    fake_gh_fixture(
+        &temp_dir,
+        "prs/org1_api.json",
+        &format!("[{}]", review_requested_pr(1, "Change", "testuser")),
+    );
+
+    let run = |extra: &[&str]| {
+        let output = Command::cargo_bin("rr")
+            .unwrap()
+            .args(["--format", "json", "--conflicts-only"])
+            .args(extra)
+            .xdg_dirs(&config_dir)
+            .env("PATH", &path)
+            .output()
+            .unwrap();
+        serde_json::from_slice::<serde_json::Value>(&output.stdout).unwrap()
+    };
+
+    let with_metadata = run(&[]);
+    assert_eq!(with_metadata["metadata"]["repo_pattern"], "a.*");
+    assert_eq!(with_metadata["metadata"]["filters"][0], "--conflicts-only");
+    assert!(run(&["--no-metadata"]).is_array());
+}
+
+#[test]
+fn test_sort_and_concurrency_defaults() {
+    let temp_dir = TempDir::new().unwrap();
+    let config_dir = create_test_config(&temp_dir, vec!["org1"], "testuser", None);
+    use_legacy_scan(&temp_dir);
+    let config_path = temp_dir.path().join("config/review-radar/config.toml");
+    let mut config = Config::load_from_path(&config_path).unwrap();
+    config.defaults.sort = Some(review_radar::SortKey::Title);
+    config.defaults.concurrency = Some(0);
+    config.save_to_path(&config_path).unwrap();
+    let path = install_fake_gh(&temp_dir);
+    fake_gh_fixture(&temp_dir, "repos/org1.json", r#"[{"name":"api"}]"#);
+    fake_gh_fixture(
+        &temp_dir,
+        "prs/org1_api.json",
+        &format!(
+            "[{},{}]",
+            review_requested_pr(1, "Beta", "testuser"),
+            review_requested_pr(2, "Alpha", "testuser")
+        ),
+    );
+
+    let run = |extra: &[&str]| {
+        let output = Command::cargo_bin("rr")
+            .unwrap()
+            .args(["--format", "urls"])
+            .args(extra)
+            .xdg_dirs(&config_dir)
+            .env("PATH", &path)
+            .output()
+            .unwrap();
+        assert!(output.status.success());
+        String::from_utf8(output.stdout).unwrap()
+    };
+
+    assert_eq!(
+        run(&[]),
+        "https://github.com/pull/2\nhttps://github.com/pull/1\n"
+    );
+    assert_eq!(
+        run(&["--sort", "number", "--concurrency", "2"]),
+        "https://github.com/pull/1\nhttps://github.com/pull/2\n"
+    );
+}
+
+#[test]
+fn test_concurrency() {
+    let temp_dir = TempDir::new().unwrap();
+    let config_dir = create_test_config(&temp_dir, vec!["org1"], "testuser", None);
+    use_legacy_scan(&temp_dir);
+    let path = install_fake_gh(&temp_dir);
+    fake_gh_fixture(
+        &temp_dir,
+        "repos/org1.json",
+        r#"[{"name":"web"},{"name":"api"},{"name":"locked"},{"name":"docs"}]"#,
+    );
+    for (repo, number) in [("web", 1), ("api", 2), ("docs", 3)] {
+        fake_gh_fixture(
+            &temp_dir,
+            &format!("prs/org1_{}.json", repo),
+            &format!("[{}]", review_requested_pr(number, "Change", "testuser")),
+        );
+    }
+
+    let run = |concurrency: &str| {
+        let output = Command::cargo_bin("rr")
+            .unwrap()
+            .args(["--concurrency", concurrency, "--format", "urls"])
+            .xdg_dirs(&config_dir)
+            .env("PATH", &path)
+            .output()
+            .unwrap();
+        assert!(output.status.success());
+        String::from_utf8(output.stdout).unwrap()
+    };
+
+    // The inaccessible repository doesn't stop the others, and results come
+    // out by repository whatever order the calls finish in
+    let expected =
+        "https://github.com/pull/2\nhttps://github.com/pull/3\nhttps://github.com/pull/1\n";
+    assert_eq!(run("8"), expected);
+    // 0 is clamped to a single call at a time
+    assert_eq!(run("0"), expected);
+
+    Command::cargo_bin("rr")
+        .unwrap()
+        .args(["--concurrency", "many"])
+        .xdg_dirs(&config_dir)
+        .env("PATH", &path)
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("invalid value 'many'"));
+}
+
+#[test]
+fn test_json_flag_empty_result() {
+    let temp_dir = TempDir::new().unwrap();
+    let config_dir = create_test_config(&temp_dir, vec!["org1"], "testuser", None);
+    let path = install_fake_gh(&temp_dir);
+    fake_gh_fixture(&temp_dir, "repos/org1.json", r#"[{"name":"api"}]"#);
+    fake_gh_fixture(&temp_dir, "prs/org1_api.json", "[]");
+
+    let output = Command::cargo_bin("rr")
+        .unwrap()
+        .arg("--json")
+        .xdg_dirs(&config_dir)
+        .env("PATH", &path)
+        .output()
+        .unwrap();
+
+    assert!(output.status.success());
+    let prs: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+    assert_eq!(prs, serde_json::json!([]));
+    assert!(String::from_utf8_lossy(&output.stderr).contains("org1"));
+}
+
+#[test]
+fn test_single_repo_positional() {
+    let temp_dir = TempDir::new().unwrap();
+    let config_dir = create_test_config(&temp_dir, vec!["org1"], "testuser", Some("^web"));
+    let path = install_fake_gh(&temp_dir);
+    fake_gh_fixture(
+        &temp_dir,
+        "prs/other_api.json",
+        &format!("[{}]", review_requested_pr(1, "Change", "testuser")),
+    );
+
+    let mut cmd = Command::cargo_bin("rr").unwrap();
+    cmd.arg("other/api")
+        .xdg_dirs(&config_dir)
+        .env("PATH", &path)
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("#1 - Change"));
+
+    let calls = fake_gh_calls(&temp_dir);
+    assert!(!calls.contains("repo list"));
+    assert_eq!(calls.matches("pr list").count(), 1);
+
+    let mut cmd = Command::cargo_bin("rr").unwrap();
+    cmd.arg("not-a-repo")
+        .xdg_dirs(&config_dir)
+        .env("PATH", &path)
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("use the OWNER/REPO form"));
+
+    // Subcommands still take precedence
+    let mut cmd = Command::cargo_bin("rr").unwrap();
+    cmd.arg("config")
+        .xdg_dirs(&config_dir)
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("Current configuration"));
+}
+
+#[test]
+fn test_age_colors() {
+    let temp_dir = TempDir::new().unwrap();
+    let config_dir = create_test_config(&temp_dir, vec!["org1"], "testuser", None);
+    let config_path = std::path::Path::new(&config_dir)
+        .join("review-radar")
+        .join("config.toml");
+    let mut content = fs::read_to_string(&config_path).unwrap();
+    content.push_str("\n[age_colors]\nyellow = 2000000\nred = 3000000\n");
+    fs::write(&config_path, content).unwrap();
+
+    let path = install_fake_gh(&temp_dir);
+    fake_gh_fixture(&temp_dir, "repos/org1.json", r#"[{"name":"api"}]"#);
+    fake_gh_fixture(
+        &temp_dir,
+        "prs/org1_api.json",
+        r#"[{"number":1,"title":"Old","url":"u1","author":{"login":"alice"},
+             "reviewRequests":[{"login":"testuser"}],"createdAt":"2020-01-01T00:00:00Z"}]"#,
+    );
+
+    // Green, since the configured thresholds are thousands of years away
+    let mut cmd = Command::cargo_bin("rr").unwrap();
+    cmd.args(["--color", "always"])
+        .xdg_dirs(&config_dir)
+        .env("PATH", &path)
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("\x1b[32m🔗 #1 - Old\x1b[0m"))
+        .stdout(predicate::str::contains("⏳ Waiting: "));
+
+    let mut cmd = Command::cargo_bin("rr").unwrap();
+    cmd.args(["--color", "never"])
+        .xdg_dirs(&config_dir)
+        .env("PATH", &path)
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("\x1b[").not());
+}
+
+#[test]
+fn test_orgs_sync() {
+    let temp_dir = TempDir::new().unwrap();
+    let config_dir = create_test_config(&temp_dir, vec!["org1", "public-org"], "testuser", None);
+    let path = install_fake_gh(&temp_dir);
+    fake_gh_fixture(&temp_dir, "api/user_orgs", "org1\norg2\norg3\n");
+
+    // Changes are previewed but need --yes without a terminal to confirm on
+    let mut cmd = Command::cargo_bin("rr").unwrap();
+    cmd.args(["orgs", "sync", "--all"])
+        .xdg_dirs(&config_dir)
+        .env("PATH", &path)
+        .assert()
+        .failure()
+        .stdout(predicate::str::contains("  + org2\n  + org3\n"))
+        .stderr(predicate::str::contains("pass --yes"));
+
+    let mut cmd = Command::cargo_bin("rr").unwrap();
+    cmd.args(["orgs", "sync", "--all", "--yes"])
+        .xdg_dirs(&config_dir)
+        .env("PATH", &path)
+        .assert()
+        .success()
+        .stdout(predicate::str::contains(
+            "Configuration updated successfully",
+        ));
+
+    let config_path = std::path::Path::new(&config_dir)
+        .join("review-radar")
+        .join("config.toml");
+    let config = Config::load_from_path(&config_path).unwrap();
+    assert_eq!(config.orgs, ["org1", "public-org", "org2", "org3"]);
+    assert!(fake_gh_calls(&temp_dir).contains("api user/orgs --paginate"));
+
+    let mut cmd = Command::cargo_bin("rr").unwrap();
+    cmd.args(["orgs", "sync"])
+        .xdg_dirs(&config_dir)
+        .env("PATH", &path)
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("pass --all"));
+}
+
+#[test]
+fn test_quiet_prints_only_results() {
+    let temp_dir = TempDir::new().unwrap();
+    let config_dir = create_test_config(&temp_dir, vec!["org1"], "testuser", None);
+    let path = install_fake_gh(&temp_dir);
+    fake_gh_fixture(&temp_dir, "repos/org1.json", r#"[{"name":"api"}]"#);
+    fake_gh_fixture(
+        &temp_dir,
+        "prs/org1_api.json",
+        &format!("[{}]", review_requested_pr(1, "Change", "testuser")),
+    );
+
+    let output = Command::cargo_bin("rr")
+        .unwrap()
+        .arg("--quiet")
+        .xdg_dirs(&config_dir)
+        .env("PATH", &path)
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    assert!(stdout.starts_with("📋 Found 1 PR(s)"), "{:?}", stdout);
+    assert!(stdout.ends_with("/pull/1\n"), "{:?}", stdout);
+    assert!(output.stderr.is_empty());
+}
+
+#[test]
+fn test_gh_arg_passthrough() {
+    let temp_dir = TempDir::new().unwrap();
+    let config_dir = create_test_config(&temp_dir, vec!["org1"], "testuser", None);
+    let path = install_fake_gh(&temp_dir);
+    fake_gh_fixture(&temp_dir, "repos/org1.json", r#"[{"name":"api"}]"#);
+    fake_gh_fixture(&temp_dir, "prs/org1_api.json", "[]");
+
+    let mut cmd = Command::cargo_bin("rr").unwrap();
+    cmd.args(["--gh-arg", "--label", "--gh-arg=urgent"])
+        .xdg_dirs(&config_dir)
+        .env("PATH", &path)
+        .assert()
+        .success();
+
+    let calls = fake_gh_calls(&temp_dir);
+    let pr_list = calls
+        .lines()
+        .find(|call| call.starts_with("pr list"))
+        .unwrap();
+    assert!(pr_list.ends_with(" --label urgent"), "{}", pr_list);
+}
+
+#[test]
+fn test_raw_gh_format() {
+    let temp_dir = TempDir::new().unwrap();
+    let config_dir = create_test_config(&temp_dir, vec!["org1"], "testuser", None);
+    let path = install_fake_gh(&temp_dir);
+    fake_gh_fixture(&temp_dir, "repos/org1.json", r#"[{"name":"api"}]"#);
+    fake_gh_fixture(
+        &temp_dir,
+        "prs/org1_api.json",
+        r#"[{"number":1,"title":"Mine","url":"https://github.com/pull/1","author":{"login":"alice"},"isDraft":true,"labels":[{"name":"urgent"}],"reviewRequests":[{"login":"someone"},{"login":"testuser"}]},
+           {"number":2,"title":"Other","url":"https://github.com/pull/2","author":{"login":"alice"},"reviewRequests":[{"login":"someone"}]}]"#,
+    );
+
+    let mut cmd = Command::cargo_bin("rr").unwrap();
+    let output = cmd
+        .args(["--format", "raw-gh"])
+        .xdg_dirs(&config_dir)
+        .env("PATH", &path)
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+
+    // Fields rr doesn't model and the other reviewers are kept as gh reported them
+    let raw: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+    assert_eq!(raw.as_array().unwrap().len(), 1);
+    assert_eq!(raw[0]["number"], 1);
+    assert_eq!(raw[0]["isDraft"], true);
+    assert_eq!(raw[0]["labels"][0]["name"], "urgent");
+    assert_eq!(raw[0]["reviewRequests"].as_array().unwrap().len(), 2);
+
+    let calls = fake_gh_calls(&temp_dir);
+    assert!(calls.contains("isDraft,labels"), "{}", calls);
+    assert!(!calls.contains("--jq"), "{}", calls);
+}
+
+#[test]
+fn test_pattern_match_command() {
+    let mut cmd = Command::cargo_bin("rr").unwrap();
+    cmd.args(["pattern-match", "^void-", "void-api", "my-void", "void-web"])
+        .env("PATH", "")
+        .assert()
+        .success()
+        .stdout(predicate::str::contains(
+            "✅ void-api\n❌ my-void\n✅ void-web\n",
+        ))
+        .stdout(predicate::str::contains("2 of 3 names match '^void-'"));
+
+    let mut cmd = Command::cargo_bin("rr").unwrap();
+    cmd.args(["pattern-match", "void-(", "void-api"])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("Invalid regex pattern 'void-('"));
+}
+
+#[test]
+fn test_duplicate_orgs_are_scanned_once() {
+    let temp_dir = TempDir::new().unwrap();
+    let config_dir = create_test_config(&temp_dir, vec!["org2"], "testuser", None);
+    let path = install_fake_gh(&temp_dir);
+    fake_gh_fixture(&temp_dir, "repos/org1.json", r#"[{"name":"api"}]"#);
+    fake_gh_fixture(&temp_dir, "repos/org2.json", r#"[{"name":"web"}]"#);
+    fake_gh_fixture(&temp_dir, "prs/org1_api.json", "[]");
+    fake_gh_fixture(&temp_dir, "prs/org2_web.json", "[]");
+    fake_gh_fixture(&temp_dir, "api/users_org1", "");
+    fake_gh_fixture(&temp_dir, "api/users_org2", "");
+
+    let mut cmd = Command::cargo_bin("rr").unwrap();
+    cmd.args(["--orgs", "org1,org1,org2"])
+        .xdg_dirs(&config_dir)
+        .env("PATH", &path)
+        .assert()
+        .success()
+        .stderr(predicate::str::contains("ignoring repeats of: org1"));
+
+    let calls = fake_gh_calls(&temp_dir);
+    let searches: Vec<&str> = calls
+        .lines()
+        .filter(|line| line.contains("is:open"))
+        .collect();
+    assert_eq!(searches.len(), 1);
+    assert_eq!(searches[0].matches("org:org1").count(), 1);
+    assert_eq!(searches[0].matches("org:org2").count(), 1);
+}